@@ -0,0 +1,701 @@
+use std::fmt::Display;
+
+use super::{
+    aabb::Aabb,
+    arraynd::{fmt_cells, ArrayNd},
+    linear_index::LinearIndex,
+    permutations::PermId,
+    vector::Vector,
+};
+
+// NOTE: Describes where the cells of a view live inside the borrowed buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Layout<const N: usize> {
+    offset: usize,
+    dims: [usize; N],
+    strides: [isize; N],
+}
+
+impl<const N: usize> Layout<N> {
+    fn of<T>(array: &ArrayNd<N, T>) -> Self {
+        Self {
+            offset: 0,
+            dims: array.dims,
+            strides: array.dim_strides.map(|s| s as isize),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    fn linear(&self, p: Vector<N, i32>) -> Option<usize> {
+        let mut result = self.offset as isize;
+        for ((&x, &d), &s) in p.values.iter().zip(&self.dims).zip(&self.strides) {
+            if x < 0 || x as usize >= d {
+                return None;
+            }
+            result += x as isize * s;
+        }
+        Some(result as usize)
+    }
+
+    fn coordinates(&self) -> impl Iterator<Item = Vector<N, i32>> {
+        let dims = Vector::new(self.dims);
+        (0..self.len()).map(move |i| {
+            let p: Vector<N, usize> = dims.unindex(i).unwrap();
+            p.try_into().unwrap()
+        })
+    }
+
+    fn window(mut self, aabb: Aabb<N, i32>) -> Option<Self> {
+        let start = self.linear(aabb.min)?;
+        self.linear(aabb.max)?;
+        if (0..N).any(|i| aabb.min.values[i] > aabb.max.values[i]) {
+            return None;
+        }
+
+        self.offset = start;
+        for i in 0..N {
+            self.dims[i] = (aabb.max.values[i] - aabb.min.values[i]) as usize + 1;
+        }
+        Some(self)
+    }
+
+    fn swap_axes(mut self, a: usize, b: usize) -> Self {
+        self.dims.swap(a, b);
+        self.strides.swap(a, b);
+        self
+    }
+
+    fn permuted_axes(self, order: [usize; N]) -> Self {
+        let mut used = [false; N];
+        for &axis in &order {
+            assert!(
+                axis < N && !used[axis],
+                "axis order has to be a permutation"
+            );
+            used[axis] = true;
+        }
+
+        Self {
+            offset: self.offset,
+            dims: order.map(|axis| self.dims[axis]),
+            strides: order.map(|axis| self.strides[axis]),
+        }
+    }
+
+    fn transposed(mut self) -> Self {
+        self.dims.reverse();
+        self.strides.reverse();
+        self
+    }
+
+    fn flipped(mut self, axis: usize) -> Self {
+        if self.dims[axis] > 0 {
+            self.offset = (self.offset as isize
+                + (self.dims[axis] - 1) as isize * self.strides[axis])
+                as usize;
+        }
+        self.strides[axis] = -self.strides[axis];
+        self
+    }
+}
+
+fn collect_array<const N: usize, U>(dims: [usize; N], data: Vec<U>) -> ArrayNd<N, U> {
+    let mut dim_strides = [0; N];
+    let mut current_stride = 1;
+    for i in 0..N {
+        dim_strides[i] = current_stride;
+        current_stride *= dims[i];
+    }
+
+    ArrayNd {
+        data,
+        dims,
+        dim_strides,
+    }
+}
+
+// NOTE: Read-only window into an `ArrayNd`. Sub-windows, transpositions and flips only adjust the
+// offset and strides, the underlying buffer is never copied.
+#[derive(Debug)]
+pub struct ArrayView<'a, const N: usize, T> {
+    data: &'a [T],
+    layout: Layout<N>,
+}
+
+impl<'a, const N: usize, T> Clone for ArrayView<'a, N, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, const N: usize, T> Copy for ArrayView<'a, N, T> {}
+
+impl<'a, const N: usize, T> ArrayView<'a, N, T> {
+    pub fn new(array: &'a ArrayNd<N, T>) -> Self {
+        Self {
+            data: &array.data,
+            layout: Layout::of(array),
+        }
+    }
+
+    pub fn dims(&self) -> [usize; N] {
+        self.layout.dims
+    }
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, p: Vector<N, i32>) -> Option<&'a T> {
+        self.layout.linear(p).map(|i| &self.data[i])
+    }
+
+    // NOTE: Iterates in storage order of the view, first axis changing fastest.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector<N, i32>, &'a T)> + 'a {
+        let view = *self;
+        self.layout
+            .coordinates()
+            .map(move |p| (p, &view.data[view.layout.linear(p).unwrap()]))
+    }
+
+    // NOTE: Inclusive window in view coordinates. `None` if `aabb` does not lie inside the view.
+    pub fn window(self, aabb: Aabb<N, i32>) -> Option<Self> {
+        Some(Self {
+            data: self.data,
+            layout: self.layout.window(aabb)?,
+        })
+    }
+    pub fn swap_axes(self, a: usize, b: usize) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.swap_axes(a, b),
+        }
+    }
+    // NOTE: Axis `i` of the result is axis `order[i]` of `self`.
+    pub fn permuted_axes(self, order: [usize; N]) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.permuted_axes(order),
+        }
+    }
+    // NOTE: Reverses the order of all axes. For 2D views this is the usual transpose.
+    pub fn transposed(self) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.transposed(),
+        }
+    }
+    pub fn flipped(self, axis: usize) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.flipped(axis),
+        }
+    }
+
+    pub fn map<F, U>(&self, f: F) -> ArrayNd<N, U>
+    where
+        F: Fn(&T) -> U,
+    {
+        let data = self.iter().map(|(_, v)| f(v)).collect();
+        collect_array(self.dims(), data)
+    }
+}
+
+impl<'a, const N: usize, T: Clone> ArrayView<'a, N, T> {
+    pub fn to_array(&self) -> ArrayNd<N, T> {
+        self.map(T::clone)
+    }
+}
+
+impl<'a, const N: usize, T: Display> Display for ArrayView<'a, N, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dims = Vector::new(self.dims());
+        fmt_cells(f, self.dims(), |index| {
+            let p: Vector<N, usize> = dims.unindex(index).unwrap();
+            self.get(p.try_into().unwrap()).unwrap()
+        })
+    }
+}
+
+// NOTE: Mutable counterpart of `ArrayView`.
+#[derive(Debug)]
+pub struct ArrayViewMut<'a, const N: usize, T> {
+    data: &'a mut [T],
+    layout: Layout<N>,
+}
+
+impl<'a, const N: usize, T> ArrayViewMut<'a, N, T> {
+    pub fn new(array: &'a mut ArrayNd<N, T>) -> Self {
+        let layout = Layout::of(array);
+        Self {
+            data: &mut array.data,
+            layout,
+        }
+    }
+
+    pub fn view(&self) -> ArrayView<'_, N, T> {
+        ArrayView {
+            data: self.data,
+            layout: self.layout,
+        }
+    }
+
+    pub fn dims(&self) -> [usize; N] {
+        self.layout.dims
+    }
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, p: Vector<N, i32>) -> Option<&T> {
+        self.layout.linear(p).map(|i| &self.data[i])
+    }
+    pub fn get_mut(&mut self, p: Vector<N, i32>) -> Option<&mut T> {
+        self.layout.linear(p).map(|i| &mut self.data[i])
+    }
+    pub fn set(&mut self, p: Vector<N, i32>, v: T) -> bool {
+        if let Some(x) = self.get_mut(p) {
+            *x = v;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vector<N, i32>, &T)> {
+        self.view().iter()
+    }
+    // NOTE: A strided layout can not hand out `&mut T` through a safe iterator, so mutation
+    // goes through a callback instead.
+    pub fn for_each_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Vector<N, i32>, &mut T),
+    {
+        for p in self.layout.coordinates() {
+            let i = self.layout.linear(p).unwrap();
+            f(p, &mut self.data[i]);
+        }
+    }
+
+    pub fn window(self, aabb: Aabb<N, i32>) -> Option<Self> {
+        let layout = self.layout.window(aabb)?;
+        Some(Self {
+            data: self.data,
+            layout,
+        })
+    }
+    pub fn swap_axes(self, a: usize, b: usize) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.swap_axes(a, b),
+        }
+    }
+    // NOTE: Axis `i` of the result is axis `order[i]` of `self`.
+    pub fn permuted_axes(self, order: [usize; N]) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.permuted_axes(order),
+        }
+    }
+    pub fn transposed(self) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.transposed(),
+        }
+    }
+    pub fn flipped(self, axis: usize) -> Self {
+        Self {
+            data: self.data,
+            layout: self.layout.flipped(axis),
+        }
+    }
+
+    pub fn map<F, U>(&self, f: F) -> ArrayNd<N, U>
+    where
+        F: Fn(&T) -> U,
+    {
+        self.view().map(f)
+    }
+}
+
+impl<'a, const N: usize, T: Clone> ArrayViewMut<'a, N, T> {
+    pub fn fill(&mut self, v: T) {
+        self.for_each_mut(|_, x| *x = v.clone());
+    }
+    pub fn to_array(&self) -> ArrayNd<N, T> {
+        self.view().to_array()
+    }
+}
+
+impl<'a, const N: usize, T: Display> Display for ArrayViewMut<'a, N, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+impl<const N: usize, T> ArrayNd<N, T> {
+    pub fn view(&self) -> ArrayView<'_, N, T> {
+        ArrayView::new(self)
+    }
+    pub fn view_mut(&mut self) -> ArrayViewMut<'_, N, T> {
+        ArrayViewMut::new(self)
+    }
+    pub fn window(&self, aabb: Aabb<N, i32>) -> Option<ArrayView<'_, N, T>> {
+        self.view().window(aabb)
+    }
+    pub fn window_mut(&mut self, aabb: Aabb<N, i32>) -> Option<ArrayViewMut<'_, N, T>> {
+        self.view_mut().window(aabb)
+    }
+}
+
+// NOTE: The 8 symmetries of a rectangle, in screen coordinates (y pointing down).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dihedral {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Dihedral {
+    pub const ALL: [Self; 8] = [
+        Self::Identity,
+        Self::RotateCw,
+        Self::Rotate180,
+        Self::RotateCcw,
+        Self::FlipHorizontal,
+        Self::FlipVertical,
+        Self::Transpose,
+        Self::AntiTranspose,
+    ];
+
+    pub fn is_rotation(&self) -> bool {
+        matches!(
+            self,
+            Self::Identity | Self::RotateCw | Self::Rotate180 | Self::RotateCcw
+        )
+    }
+
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::RotateCw => Self::RotateCcw,
+            Self::RotateCcw => Self::RotateCw,
+            x => *x,
+        }
+    }
+}
+
+impl<'a, T> ArrayView<'a, 2, T> {
+    pub fn transformed(self, transform: Dihedral) -> Self {
+        match transform {
+            Dihedral::Identity => self,
+            Dihedral::RotateCw => self.transposed().flipped(0),
+            Dihedral::Rotate180 => self.flipped(0).flipped(1),
+            Dihedral::RotateCcw => self.transposed().flipped(1),
+            Dihedral::FlipHorizontal => self.flipped(0),
+            Dihedral::FlipVertical => self.flipped(1),
+            Dihedral::Transpose => self.transposed(),
+            Dihedral::AntiTranspose => self.transposed().flipped(0).flipped(1),
+        }
+    }
+}
+
+impl<T: Clone> ArrayNd<2, T> {
+    pub fn transformed(&self, transform: Dihedral) -> Self {
+        self.view().transformed(transform).to_array()
+    }
+}
+
+impl<const N: usize, T: Clone> ArrayNd<N, T> {
+    // NOTE: Axis `i` of the result is axis `order[i]` of `self`.
+    pub fn permuted_axes(&self, order: [usize; N]) -> Self {
+        self.view().permuted_axes(order).to_array()
+    }
+
+    pub fn reversed_axes(&self, reversed: [bool; N]) -> Self {
+        let mut view = self.view();
+        for (axis, _) in reversed.iter().enumerate().filter(|(_, r)| **r) {
+            view = view.flipped(axis);
+        }
+        view.to_array()
+    }
+}
+
+impl<const N: usize, T> ArrayNd<N, T> {
+    // NOTE: Every combination of an axis permutation and a set of reversed axes, i.e. all the
+    // symmetries of an N dimensional box. For N = 2 these are the 8 dihedral transforms.
+    fn oriented_views(&self, rotations_only: bool) -> impl Iterator<Item = ArrayView<'_, N, T>> {
+        let permutations: usize = (1..=N).product();
+        (0..permutations).flat_map(move |id| {
+            let order = PermId::<N>(id).perm().0;
+            let odd_permutation = (0..N)
+                .flat_map(|i| (i + 1..N).map(move |j| (i, j)))
+                .filter(|&(i, j)| order[i] > order[j])
+                .count()
+                % 2
+                == 1;
+
+            (0..1usize << N).filter_map(move |mask| {
+                let odd_reversal = mask.count_ones() % 2 == 1;
+                if rotations_only && odd_permutation != odd_reversal {
+                    return None;
+                }
+
+                let mut view = self.view().permuted_axes(order);
+                for axis in (0..N).filter(|axis| mask & (1 << axis) != 0) {
+                    view = view.flipped(axis);
+                }
+                Some(view)
+            })
+        })
+    }
+}
+
+impl<const N: usize, T: Clone + PartialEq> ArrayNd<N, T> {
+    fn distinct(views: impl Iterator<Item = Self>) -> impl Iterator<Item = Self> {
+        let mut seen: Vec<Self> = vec![];
+        views.filter(move |a| {
+            if seen.contains(a) {
+                false
+            } else {
+                seen.push(a.clone());
+                true
+            }
+        })
+    }
+
+    // NOTE: All distinct arrays obtainable by permuting and reversing axes, starting with `self`.
+    pub fn orientations(&self) -> impl Iterator<Item = Self> + '_ {
+        Self::distinct(self.oriented_views(false).map(|v| v.to_array()))
+    }
+
+    // NOTE: Like `ArrayNd::orientations`, but without mirror images.
+    pub fn rotations(&self) -> impl Iterator<Item = Self> + '_ {
+        Self::distinct(self.oriented_views(true).map(|v| v.to_array()))
+    }
+}
+
+impl<const N: usize, T: Clone + Ord> ArrayNd<N, T> {
+    // NOTE: The orientation with the lexicographically smallest `(dims, data)`. Two arrays are
+    // equal up to symmetry exactly when their canonical forms are equal.
+    pub fn canonical(&self) -> Self {
+        self.oriented_views(false)
+            .map(|v| v.to_array())
+            .min_by(|a, b| (a.dims, &a.data).cmp(&(b.dims, &b.data)))
+            .unwrap()
+    }
+
+    pub fn canonical_rotation(&self) -> Self {
+        self.oriented_views(true)
+            .map(|v| v.to_array())
+            .min_by(|a, b| (a.dims, &a.data).cmp(&(b.dims, &b.data)))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::Dihedral;
+    use crate::{
+        aabb::Aabb2,
+        arraynd::{Array2d, Array3d, CharArray2d},
+        vector::{V2, V3},
+    };
+
+    fn numbers() -> Array2d<i32> {
+        Array2d::from_slice([4, 3], &(0..12).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn view_matches_array() {
+        let a = numbers();
+        let v = a.view();
+        assert_eq!(v.dims(), [4, 3]);
+        assert_eq!(v.get(V2::from_xy(1, 2)), a.get(V2::from_xy(1, 2)));
+        assert_eq!(v.get(V2::from_xy(4, 0)), None);
+        assert_eq!(v.to_array(), a);
+        assert_eq!(v.to_string(), a.to_string());
+    }
+
+    #[test]
+    fn window() {
+        let a = numbers();
+        let w = a
+            .window(Aabb2::new(V2::from_xy(1, 1), V2::from_xy(2, 2)))
+            .unwrap();
+        assert_eq!(w.dims(), [2, 2]);
+        assert_eq!(w.get(V2::from_xy(0, 0)), Some(&5));
+        assert_eq!(
+            w.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec![5, 6, 9, 10]
+        );
+        assert_eq!(w.to_string(), "56\n910\n");
+
+        let inner = w
+            .window(Aabb2::new(V2::from_xy(1, 0), V2::from_xy(1, 1)))
+            .unwrap();
+        assert_eq!(inner.map(|x| x * 10).data, vec![60, 100]);
+
+        assert!(a
+            .window(Aabb2::new(V2::from_xy(2, 2), V2::from_xy(4, 2)))
+            .is_none());
+        assert!(a
+            .window(Aabb2::new(V2::from_xy(2, 2), V2::from_xy(1, 2)))
+            .is_none());
+    }
+
+    #[test]
+    fn transposed_and_flipped() {
+        let a = numbers();
+        let t = a.view().transposed();
+        assert_eq!(t.dims(), [3, 4]);
+        assert_eq!(t.get(V2::from_xy(2, 1)), Some(&9));
+        assert_eq!(
+            t.to_array().data,
+            vec![0, 4, 8, 1, 5, 9, 2, 6, 10, 3, 7, 11]
+        );
+        assert_eq!(t.transposed().to_array(), a);
+
+        let fx = a.view().flipped(0);
+        assert_eq!(
+            fx.to_array().data,
+            vec![3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8]
+        );
+        let fy = a.view().flipped(1);
+        assert_eq!(fy.get(V2::from_xy(0, 0)), Some(&8));
+        assert_eq!(a.view().flipped(0).flipped(0).to_array(), a);
+
+        let w = a
+            .view()
+            .flipped(1)
+            .window(Aabb2::new(V2::from_xy(0, 0), V2::from_xy(1, 1)))
+            .unwrap();
+        assert_eq!(w.to_array().data, vec![8, 9, 4, 5]);
+    }
+
+    #[test]
+    fn swap_axes_3d() {
+        let a = Array3d::from_slice([2, 3, 4], &(0..24).collect::<Vec<_>>());
+        let s = a.view().swap_axes(0, 2);
+        assert_eq!(s.dims(), [4, 3, 2]);
+        for (p, v) in s.iter() {
+            let [x, y, z] = p.values;
+            assert_eq!(a.get(V3::from_xyz(z, y, x)), Some(v));
+        }
+    }
+
+    #[test]
+    fn view_mut_writes_through() {
+        let mut a = numbers();
+        {
+            let mut w = a
+                .window_mut(Aabb2::new(V2::from_xy(1, 0), V2::from_xy(3, 1)))
+                .unwrap()
+                .flipped(0);
+            assert!(w.set(V2::from_xy(0, 0), -1));
+            assert!(!w.set(V2::from_xy(3, 0), -1));
+            w.for_each_mut(|p, v| {
+                if p.values[1] == 1 {
+                    *v = 100 + p.values[0];
+                }
+            });
+        }
+        assert_eq!(a.data, vec![0, 1, 2, -1, 4, 102, 101, 100, 8, 9, 10, 11]);
+
+        a.view_mut().transposed().fill(7);
+        assert!(a.data.iter().all(|x| *x == 7));
+    }
+
+    #[test]
+    fn dihedral() {
+        let a = CharArray2d::from_str("ab.\ncd.\n").unwrap();
+        let t = |d| a.transformed(d).to_string();
+        assert_eq!(t(Dihedral::Identity), "ab.\ncd.\n");
+        assert_eq!(t(Dihedral::RotateCw), "ca\ndb\n..\n");
+        assert_eq!(t(Dihedral::RotateCcw), "..\nbd\nac\n");
+        assert_eq!(t(Dihedral::Rotate180), ".dc\n.ba\n");
+        assert_eq!(t(Dihedral::FlipHorizontal), ".ba\n.dc\n");
+        assert_eq!(t(Dihedral::FlipVertical), "cd.\nab.\n");
+        assert_eq!(t(Dihedral::Transpose), "ac\nbd\n..\n");
+        assert_eq!(t(Dihedral::AntiTranspose), "..\ndb\nca\n");
+
+        for d in Dihedral::ALL {
+            assert_eq!(a.transformed(d).transformed(d.inverse()), a);
+        }
+        assert_eq!(
+            a.transformed(Dihedral::RotateCw)
+                .transformed(Dihedral::RotateCw),
+            a.transformed(Dihedral::Rotate180)
+        );
+    }
+
+    #[test]
+    fn permuted_and_reversed_axes() {
+        let a = Array3d::from_slice([2, 3, 4], &(0..24).collect::<Vec<_>>());
+        let p = a.permuted_axes([2, 0, 1]);
+        assert_eq!(p.dims, [4, 2, 3]);
+        assert_eq!(p.get(V3::from_xyz(3, 1, 2)), a.get(V3::from_xyz(1, 2, 3)));
+
+        let r = a.reversed_axes([true, false, true]);
+        assert_eq!(r.get(V3::from_xyz(0, 1, 0)), a.get(V3::from_xyz(1, 1, 3)));
+        assert_eq!(r.reversed_axes([true, false, true]), a);
+    }
+
+    #[test]
+    fn orientations() {
+        let a = CharArray2d::from_str("ab\ncd\n").unwrap();
+        let all: Vec<_> = a.orientations().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], a);
+        for d in Dihedral::ALL {
+            assert!(all.contains(&a.transformed(d)));
+        }
+        let rotations: Vec<_> = a.rotations().collect();
+        assert_eq!(rotations.len(), 4);
+        for d in Dihedral::ALL.into_iter().filter(Dihedral::is_rotation) {
+            assert!(rotations.contains(&a.transformed(d)));
+        }
+
+        let symmetric = CharArray2d::from_str("#.#\n...\n#.#\n").unwrap();
+        assert_eq!(symmetric.orientations().count(), 1);
+        let l = CharArray2d::from_str("#.\n##\n").unwrap();
+        assert_eq!(l.orientations().count(), 4);
+        assert_eq!(l.rotations().count(), 4);
+
+        assert_eq!(Array3d::new([2, 2, 2], 0).orientations().count(), 1);
+        assert_eq!(
+            Array3d::from_slice([2, 3, 4], &(0..24).collect::<Vec<_>>())
+                .orientations()
+                .count(),
+            48
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let a = CharArray2d::from_str("#..\n##.\n").unwrap();
+        let canonical = a.canonical();
+        for d in Dihedral::ALL {
+            assert_eq!(a.transformed(d).canonical(), canonical);
+        }
+        assert_eq!(canonical.to_string(), "##\n#.\n..\n");
+
+        let mirrored = a.transformed(Dihedral::FlipHorizontal);
+        assert_eq!(mirrored.canonical(), canonical);
+        assert_ne!(mirrored.canonical_rotation(), a.canonical_rotation());
+    }
+}
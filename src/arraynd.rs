@@ -1,5 +1,7 @@
 use std::{
+    collections::{HashMap, VecDeque},
     fmt::Display,
+    hash::Hash,
     io::{BufRead, BufReader},
     iter,
     ops::{Index, IndexMut},
@@ -7,10 +9,13 @@ use std::{
 };
 
 use super::{
-    geometric_traits::{IterateNeighbours, IterateNeighboursContext},
+    aabb::Aabb,
+    array_view::ArrayView,
+    geometric_traits::{CoverObject, IterateNeighbours, IterateNeighboursContext},
     line::Line,
-    line_iterator::LineIterator,
+    line_iterator::{LineIterator, LineMode},
     linear_index::LinearIndex,
+    modular::ModularDecompose,
     vector::Vector,
 };
 
@@ -261,6 +266,26 @@ impl<const N: usize, T> ArrayNd<N, T> {
     }
 }
 
+impl<const N: usize, T> ArrayNd<N, T> {
+    // NOTE: Maps any position onto the array as if its opposite borders were glued together.
+    pub fn wrap(&self, p: Vector<N, i32>) -> Vector<N, i32> {
+        let dims: Vector<N, i32> = Vector::new(self.dims).try_into().unwrap();
+        p.mod_n(dims)
+    }
+
+    pub fn get_wrapping(&self, p: Vector<N, i32>) -> &T {
+        let index = self.index_unchecked(self.wrap(p)).unwrap();
+        self.get_linear(index)
+    }
+    pub fn get_mut_wrapping(&mut self, p: Vector<N, i32>) -> &mut T {
+        let index = self.index_unchecked(self.wrap(p)).unwrap();
+        self.get_mut_linear(index)
+    }
+    pub fn set_wrapping(&mut self, p: Vector<N, i32>, v: T) {
+        *self.get_mut_wrapping(p) = v;
+    }
+}
+
 impl<const N: usize, T> ArrayNd<N, T> {
     fn find_internal<F, I>(&self, predicate: F) -> Option<I>
     where
@@ -360,6 +385,14 @@ impl<const N: usize, T> ArrayNd<N, T> {
     ) -> LineIterator<B, N> {
         LineIterator::new(p0, p1)
     }
+    pub fn line_iter_with_mode<const B: bool>(
+        &self,
+        p0: Vector<N, i32>,
+        p1: Vector<N, i32>,
+        mode: LineMode,
+    ) -> LineIterator<B, N> {
+        LineIterator::with_mode(p0, p1, mode)
+    }
 }
 
 impl<const N: usize, T> ArrayNd<N, T> {
@@ -379,6 +412,16 @@ impl<const N: usize, T: Copy> ArrayNd<N, T> {
             self.set(p, v);
         }
     }
+    pub fn draw_line_with_mode<const B: bool>(
+        &mut self,
+        line: Line<Vector<N, i32>>,
+        mode: LineMode,
+        v: T,
+    ) {
+        for p in self.line_iter_with_mode::<B>(line.start, line.end, mode) {
+            self.set(p, v);
+        }
+    }
     // pub fn draw_line_from_points<const B: bool>(
     //     &mut self,
     //     p0: Vector<N, i32>,
@@ -391,246 +434,1495 @@ impl<const N: usize, T: Copy> ArrayNd<N, T> {
     // }
 }
 
-// NOTE(lubo): Choose which slice (index) to paint in each dimension, or pass None to paint all tiles in that dimension.
-// Example:
-//   Draw a plane at Y = 3 in a 3D array
-//   a (: Array3D) .draw_block(&[None, Some(3), None])
 impl<const N: usize, T: Copy> ArrayNd<N, T> {
-    // TODO(lubo): Block iterator!!
-    pub fn iter_block(&mut self, mut matching: [Option<usize>; N]) -> impl Iterator<Item = &T> {
-        todo!();
-        [].into_iter()
-        // let mut index = 0;
-        // for i in (0..N).rev() {
-        //     match matching[i] {
-        //         Some(value) => index += value * self.dim_strides[i],
-        //         None => {
-        //             for a in 0..self.dims[i] {
-        //                 matching[i] = Some(a);
-        //                 // self.draw_block(matching, v);
-        //             }
-        //             // return;
-        //         }
-        //     }
-        // }
-        // // self.set_linear(index, v)
-    }
-
-    pub fn draw_block(&mut self, mut matching: [Option<usize>; N], v: T) {
-        let mut index = 0;
-        for i in (0..N).rev() {
-            match matching[i] {
-                Some(value) => index += value * self.dim_strides[i],
-                None => {
-                    for a in 0..self.dims[i] {
-                        matching[i] = Some(a);
-                        self.draw_block(matching, v);
-                    }
-                    return;
-                }
+    // NOTE: Consecutive segments share their endpoints, so every segment after the first skips
+    // its start and each vertex is drawn once. A single point is drawn on its own.
+    pub fn draw_polyline(&mut self, points: &[Vector<N, i32>], v: T) {
+        if let [p] = points {
+            self.set(*p, v);
+        }
+        for (i, pair) in points.windows(2).enumerate() {
+            let segment = self.line_iter::<true>(pair[0], pair[1]);
+            for p in segment.skip(usize::from(i > 0)) {
+                self.set(p, v);
             }
         }
-        self.set_linear(index, v)
     }
-}
 
-impl<const C: usize, T> IterateNeighboursContext for ArrayNd<C, T> {}
+    // NOTE: Sets every cell whose centre is within `radius` of the segment, so the ends are
+    // rounded.
+    pub fn draw_thick_line(&mut self, line: Line<Vector<N, i32>>, radius: f32, v: T) {
+        let r = Vector::all(radius.max(0.0).ceil() as i32);
+        let last: Vector<N, i32> = Vector::new(self.dims).try_into().unwrap();
+        let last = last - Vector::all(1);
+        let min = line
+            .start
+            .elementwise_min(line.end)
+            .elementwise_binary(r, |a, b| (a - b).max(0));
+        let max = (line.start.elementwise_max(line.end) + r).elementwise_min(last);
+
+        let Some(mut window) = self.window_mut(Aabb::new(min, max)) else {
+            return;
+        };
+        window.for_each_mut(|p, x| {
+            if segment_distance_squared(p + min, line) <= radius * radius {
+                *x = v;
+            }
+        });
+    }
 
-impl<const C: usize, T: IterateNeighbours<()> + Copy, U> IterateNeighbours<ArrayNd<C, U>> for T
-where
-    ArrayNd<C, U>: LinearIndex<T>,
-{
-    fn neighbours(&self, _context: &ArrayNd<C, U>) -> Vec<Self> {
-        self.neighbours(&())
-            .into_iter()
-            .filter(|x| _context.is_in_bounds(x))
-            .collect()
+    pub fn draw_thick_polyline(&mut self, points: &[Vector<N, i32>], radius: f32, v: T) {
+        if let [point] = points {
+            self.draw_thick_line(Line::new(*point, *point), radius, v);
+        }
+        for pair in points.windows(2) {
+            self.draw_thick_line(Line::new(pair[0], pair[1]), radius, v);
+        }
     }
 }
 
-// TODO(lubo): Slices?
-// impl<const C: usize, T: Copy> Display for ArrayNd<C, T> {
-//     pub fn get_slice(&self, ) {
+fn segment_distance_squared<const N: usize>(p: Vector<N, i32>, line: Line<Vector<N, i32>>) -> f32 {
+    let to_f32 = |v: Vector<N, i32>| v.values.map(|x| x as f32);
+    let (p, a, b) = (to_f32(p), to_f32(line.start), to_f32(line.end));
 
-//     }
-// }
+    let mut ab_ab = 0.0;
+    let mut ap_ab = 0.0;
+    for i in 0..N {
+        ab_ab += (b[i] - a[i]) * (b[i] - a[i]);
+        ap_ab += (p[i] - a[i]) * (b[i] - a[i]);
+    }
+    let t = match ab_ab > 0.0 {
+        true => (ap_ab / ab_ab).clamp(0.0, 1.0),
+        false => 0.0,
+    };
 
-impl<const C: usize, T: Display> Display for ArrayNd<C, T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let total_size = self.data.len(); // 0..self.dims.iter().product()
-        if C > 1 {
-            let mut index = 0;
-            while index < total_size {
-                if C > 2 {
-                    let slice: Vector<C, usize> = self.unindex(index).unwrap();
-                    writeln!(f, "Slice = {}", slice)?;
-                }
+    (0..N)
+        .map(|i| {
+            let d = p[i] - (a[i] + t * (b[i] - a[i]));
+            d * d
+        })
+        .sum()
+}
 
-                for _y in 0..self.dims[1] {
-                    for _x in 0..self.dims[0] {
-                        write!(f, "{}", self.get_linear(index))?;
-                        index += 1;
-                    }
-                    writeln!(f)?;
+impl<T> Array2d<T> {
+    // NOTE: Xiaolin Wu's algorithm. `blend` receives each touched cell together with the
+    // fraction of it covered by the line. Cell centres are at integer coordinates.
+    pub fn draw_line_antialiased_with<F>(
+        &mut self,
+        from: Vector<2, f32>,
+        to: Vector<2, f32>,
+        mut blend: F,
+    ) where
+        F: FnMut(&mut T, f32),
+    {
+        let [mut x0, mut y0] = from.values;
+        let [mut x1, mut y1] = to.values;
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let mut plot = |x: i32, y: i32, coverage: f32| {
+            let p = match steep {
+                true => Vector::new([y, x]),
+                false => Vector::new([x, y]),
+            };
+            if coverage > 0.0 {
+                if let Some(cell) = self.get_mut(p) {
+                    blend(cell, coverage);
                 }
             }
-        } else {
-            for x in 0..total_size {
-                write!(f, "{}", self.get_linear(x))?;
-            }
+        };
+
+        let dx = x1 - x0;
+        let gradient = match dx == 0.0 {
+            true => 1.0,
+            false => (y1 - y0) / dx,
+        };
+        let fpart = |x: f32| x - x.floor();
+        let rfpart = |x: f32| 1.0 - fpart(x);
+
+        let mut endpoint = |x: f32, y: f32, gap: f32| {
+            let x_end = (x + 0.5).floor();
+            let y_end = y + gradient * (x_end - x);
+            let (px, py) = (x_end as i32, y_end.floor() as i32);
+            plot(px, py, rfpart(y_end) * gap);
+            plot(px, py + 1, fpart(y_end) * gap);
+            (px, y_end)
+        };
+        let (start, y_start) = endpoint(x0, y0, rfpart(x0 + 0.5));
+        let (end, _) = endpoint(x1, y1, fpart(x1 + 0.5));
+
+        let mut y = y_start + gradient;
+        for x in start + 1..end {
+            plot(x, y.floor() as i32, rfpart(y));
+            plot(x, y.floor() as i32 + 1, fpart(y));
+            y += gradient;
         }
+    }
+}
+
+impl Array2d<f32> {
+    // NOTE: Composites the coverage over the existing value, cells stay within `0..=1` if they
+    // started there.
+    pub fn draw_line_antialiased(&mut self, from: Vector<2, f32>, to: Vector<2, f32>) {
+        self.draw_line_antialiased_with(from, to, |v, coverage| *v += (1.0 - *v) * coverage);
+    }
 
-        write!(f, "")
+    pub fn draw_polyline_antialiased(&mut self, points: &[Vector<2, i32>]) {
+        let to_f32 = |p: &Vector<2, i32>| Vector::new(p.values.map(|x| x as f32));
+        for pair in points.windows(2) {
+            self.draw_line_antialiased(to_f32(&pair[0]), to_f32(&pair[1]));
+        }
     }
 }
 
-// NOTE(lubo): Specific lower dimensional arrays
+// NOTE(lubo): Choose which slice (index) to paint in each dimension, or pass None to paint all tiles in that dimension.
+// Example:
+//   Draw a plane at Y = 3 in a 3D array
+//   a (: Array3D) .draw_block(&[None, Some(3), None])
+impl<const N: usize, T> ArrayNd<N, T> {
+    pub fn iter_block(
+        &self,
+        matching: [Option<usize>; N],
+    ) -> impl Iterator<Item = (Vector<N, i32>, &T)> {
+        BlockIndices::new(self.dims, self.dim_strides, matching).map(|(p, i)| (p, &self.data[i]))
+    }
 
-pub type Array2d<T> = ArrayNd<2, T>;
-pub type Array3d<T> = ArrayNd<3, T>;
-impl<T> Array2d<T> {
-    pub fn width(&self) -> usize {
-        self.dims[0]
+    pub fn iter_block_mut(&mut self, matching: [Option<usize>; N]) -> BlockIterMut<'_, N, T> {
+        BlockIterMut {
+            indices: BlockIndices::new(self.dims, self.dim_strides, matching),
+            data: self.data.iter_mut(),
+            consumed: 0,
+        }
     }
-    pub fn height(&self) -> usize {
-        self.dims[1]
+}
+
+impl<const N: usize, T: Copy> ArrayNd<N, T> {
+    pub fn draw_block(&mut self, matching: [Option<usize>; N], v: T) {
+        for (_, x) in self.iter_block_mut(matching) {
+            *x = v;
+        }
+    }
+
+    // NOTE: Copies out the `M = N - 1` dimensional slice at `index` along `axis`.
+    // Example:
+    //   Get the plane at Z = 2 of a 3D array
+    //   a (: Array3D) .get_slice::<2>(2, 2)
+    pub fn get_slice<const M: usize>(&self, axis: usize, index: usize) -> ArrayNd<M, T> {
+        assert_eq!(
+            M + 1,
+            N,
+            "slice has to have one dimension less than the array"
+        );
+        assert!(index < self.dims[axis], "slice index out of bounds");
+
+        let mut matching = [None; N];
+        matching[axis] = Some(index);
+        let data: Vec<T> = self.iter_block(matching).map(|(_, x)| *x).collect();
+        ArrayNd::from_slice(remove_axis::<N, M>(self.dims, axis), &data)
+    }
+
+    // NOTE: Inverse of `get_slice`, overwrites the slice at `index` along `axis`.
+    pub fn set_slice<const M: usize>(&mut self, axis: usize, index: usize, slice: &ArrayNd<M, T>) {
+        assert_eq!(
+            M + 1,
+            N,
+            "slice has to have one dimension less than the array"
+        );
+        assert!(index < self.dims[axis], "slice index out of bounds");
+        assert_eq!(
+            slice.dims,
+            remove_axis::<N, M>(self.dims, axis),
+            "slice dimensions do not match the array"
+        );
+
+        let mut matching = [None; N];
+        matching[axis] = Some(index);
+        for ((_, x), v) in self.iter_block_mut(matching).zip(slice.data.iter()) {
+            *x = *v;
+        }
     }
 }
-impl<T> Array3d<T> {
-    pub fn width(&self) -> usize {
-        self.dims[0]
+
+fn remove_axis<const N: usize, const M: usize>(dims: [usize; N], axis: usize) -> [usize; M] {
+    let mut result = [0; M];
+    for (to, from) in result
+        .iter_mut()
+        .zip(dims.iter().enumerate().filter(|(i, _)| *i != axis))
+    {
+        *to = *from.1;
     }
-    pub fn height(&self) -> usize {
-        self.dims[1]
+    result
+}
+
+// NOTE: Coordinates and linear indices of the cells matching a block, in increasing linear order.
+struct BlockIndices<const N: usize> {
+    dims: [usize; N],
+    dim_strides: [usize; N],
+    matching: [Option<usize>; N],
+    at: Option<[usize; N]>,
+}
+
+impl<const N: usize> BlockIndices<N> {
+    fn new(dims: [usize; N], dim_strides: [usize; N], matching: [Option<usize>; N]) -> Self {
+        let mut at = [0; N];
+        for i in 0..N {
+            match matching[i] {
+                Some(value) if value >= dims[i] => {
+                    return Self {
+                        dims,
+                        dim_strides,
+                        matching,
+                        at: None,
+                    }
+                }
+                Some(value) => at[i] = value,
+                None => (),
+            }
+        }
+
+        Self {
+            dims,
+            dim_strides,
+            matching,
+            at: Some(at),
+        }
     }
-    pub fn depth(&self) -> usize {
-        self.dims[2]
+}
+
+impl<const N: usize> Iterator for BlockIndices<N> {
+    type Item = (Vector<N, i32>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at = self.at?;
+        let index = at.iter().zip(self.dim_strides).map(|(a, s)| a * s).sum();
+        let p = Vector::new(at).try_into().unwrap();
+
+        let mut next = at;
+        self.at = None;
+        for i in 0..N {
+            if self.matching[i].is_none() {
+                next[i] += 1;
+                if next[i] < self.dims[i] {
+                    self.at = Some(next);
+                    break;
+                }
+                next[i] = 0;
+            }
+        }
+
+        Some((p, index))
     }
 }
 
-pub type CharArray2d = Array2d<char>;
+pub struct BlockIterMut<'a, const N: usize, T> {
+    indices: BlockIndices<N>,
+    data: std::slice::IterMut<'a, T>,
+    consumed: usize,
+}
 
-impl FromStr for CharArray2d {
-    type Err = CharArrayParseError;
+impl<'a, const N: usize, T> Iterator for BlockIterMut<'a, N, T> {
+    type Item = (Vector<N, i32>, &'a mut T);
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cursor = std::io::Cursor::new(s);
-        Self::from_read(cursor)
+    fn next(&mut self) -> Option<Self::Item> {
+        let (p, index) = self.indices.next()?;
+        let x = self.data.nth(index - self.consumed)?;
+        self.consumed = index + 1;
+        Some((p, x))
     }
 }
 
-#[derive(Debug)]
-pub enum CharArrayParseError {
-    InconsistentLineWidth(usize, usize, usize, usize),
-    Io(std::io::Error),
+// NOTE: Which cells are considered adjacent. Cells are adjacent if they differ by at most one in
+// every coordinate and share a face, an edge or at least a vertex.
+// In 2D `Face` is 4-connectivity, `Edge` and `Vertex` are both 8-connectivity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    Face,
+    Edge,
+    Vertex,
 }
 
-impl Display for CharArrayParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Connectivity {
+    fn max_changed_axes(&self, n: usize) -> usize {
         match self {
-            CharArrayParseError::InconsistentLineWidth(l1, w1, l2, w2) => write!(f, "Inconsistent line width. On line {l1} the width is {w1}, while on line {l2} the width is {w2}."),
-            CharArrayParseError::Io(io) => write!(f, "IO error: {io}"),
+            Connectivity::Face => 1,
+            Connectivity::Edge => 2,
+            Connectivity::Vertex => n,
         }
     }
-}
-
-impl CharArray2d {
-    pub fn from_buffer<R: std::io::Read>(
-        reader: BufReader<R>,
-    ) -> Result<Self, CharArrayParseError> {
-        let mut array2d_width_line_number = 0;
-        let mut array2d_width = 0;
 
-        let mut data = vec![];
-        let mut height = 0;
+    pub fn offsets<const N: usize>(&self) -> Vec<Vector<N, i32>> {
+        let max_changed_axes = self.max_changed_axes(N);
+        let mut offsets = vec![];
+        let mut offset = Vector::all(-1);
+        loop {
+            let changed_axes = offset.values.iter().filter(|&&x| x != 0).count();
+            if changed_axes > 0 && changed_axes <= max_changed_axes {
+                offsets.push(offset);
+            }
 
-        for (line_number, line) in reader.lines().enumerate() {
-            let line = match line {
-                Ok(l) => l,
-                Err(e) => return Err(CharArrayParseError::Io(e)),
-            };
-            let line_width = line.len();
-            if array2d_width == 0 {
-                array2d_width = line_width;
-                array2d_width_line_number = line_number;
-            } else if array2d_width != line_width && line_width != 0 {
-                return Err(CharArrayParseError::InconsistentLineWidth(
-                    array2d_width_line_number,
-                    array2d_width,
-                    line_number,
-                    line_width,
-                ));
+            let mut axis = 0;
+            loop {
+                if axis == N {
+                    return offsets;
+                }
+                if offset.values[axis] < 1 {
+                    offset.values[axis] += 1;
+                    break;
+                }
+                offset.values[axis] = -1;
+                axis += 1;
             }
-            if line_width > 0 {
-                height += 1;
-                data.extend(line.chars());
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region<const N: usize> {
+    pub label: u32,
+    pub area: usize,
+    pub bounds: Aabb<N, i32>,
+    // NOTE: Number of cell faces the region shares with other regions or the array border.
+    // In 2D this is the perimeter, in 3D the surface area.
+    pub perimeter: usize,
+    pub centroid: Vector<N, f32>,
+}
+
+impl<const N: usize, T> ArrayNd<N, T> {
+    // NOTE: Cells reachable from `start` through cells for which `predicate` holds, `start`
+    // included. Empty if `start` is out of bounds or does not satisfy `predicate` itself.
+    pub fn flood_region_by<F>(
+        &self,
+        start: Vector<N, i32>,
+        connectivity: Connectivity,
+        predicate: F,
+    ) -> Vec<Vector<N, i32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut region = vec![];
+        let start = match self.index(start) {
+            Some(index) if predicate(self.get_linear(index)) => index,
+            _ => return region,
+        };
+
+        let offsets = connectivity.offsets::<N>();
+        let mut visited = vec![false; self.data.len()];
+        let mut open = vec![start];
+        visited[start] = true;
+        while let Some(index) = open.pop() {
+            let p: Vector<N, i32> = self.unindex(index).unwrap();
+            region.push(p);
+            for offset in offsets.iter() {
+                if let Some(n) = self.index(p + *offset) {
+                    if !visited[n] && predicate(self.get_linear(n)) {
+                        visited[n] = true;
+                        open.push(n);
+                    }
+                }
             }
         }
 
-        Ok(Self {
-            data,
-            dims: [array2d_width, height],
-            dim_strides: [1, array2d_width],
-        })
+        region
     }
 
-    pub fn from_read<R: std::io::Read>(reader: R) -> Result<Self, CharArrayParseError> {
-        Self::from_buffer(BufReader::new(reader))
+    pub fn flood_region(
+        &self,
+        start: Vector<N, i32>,
+        connectivity: Connectivity,
+    ) -> Vec<Vector<N, i32>>
+    where
+        T: PartialEq,
+    {
+        match self.get(start) {
+            Some(value) => self.flood_region_by(start, connectivity, |x| x == value),
+            None => vec![],
+        }
     }
-}
 
-impl<T: Copy> Array2d<T> {
-    pub fn with_dimensions(width: usize, height: usize, default: T) -> Self {
-        Self {
-            data: iter::repeat(default).take(width * height).collect(),
-            dims: [width, height],
-            dim_strides: [1, width],
-        }
+    // NOTE: Labels every cell, adjacent cells with equal values share a label. Labels start at 1,
+    // `regions[i]` describes the cells labelled `i + 1`.
+    pub fn label_components(&self, connectivity: Connectivity) -> (ArrayNd<N, u32>, Vec<Region<N>>)
+    where
+        T: PartialEq,
+    {
+        self.label_internal(connectivity, |_| true, |a, b| a == b)
     }
 
-    pub fn shift_n_rows_down(&mut self, n: usize, default: T) {
-        self.data.drain(..self.width() * n);
-        self.data.extend(
-            iter::repeat(default)
-                .take(self.width() * n)
-                .collect::<Vec<T>>(),
-        );
+    // NOTE: Labels connected groups of cells satisfying `predicate`, other cells get label 0.
+    pub fn label_components_by<F>(
+        &self,
+        connectivity: Connectivity,
+        predicate: F,
+    ) -> (ArrayNd<N, u32>, Vec<Region<N>>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.label_internal(connectivity, predicate, |_, _| true)
+    }
+
+    fn label_internal<F, E>(
+        &self,
+        connectivity: Connectivity,
+        include: F,
+        same: E,
+    ) -> (ArrayNd<N, u32>, Vec<Region<N>>)
+    where
+        F: Fn(&T) -> bool,
+        E: Fn(&T, &T) -> bool,
+    {
+        let offsets = connectivity.offsets::<N>();
+        let mut labels = self.map(|_| 0u32);
+        let mut sums = vec![];
+        let mut regions = vec![];
+        let mut open = vec![];
+
+        for start in 0..self.data.len() {
+            if labels.data[start] != 0 || !include(self.get_linear(start)) {
+                continue;
+            }
+
+            let label = regions.len() as u32 + 1;
+            let p: Vector<N, i32> = self.unindex(start).unwrap();
+            let mut region = Region {
+                label,
+                area: 0,
+                bounds: Aabb::new(p, p),
+                perimeter: 0,
+                centroid: Vector::all(0.0),
+            };
+            let mut sum = [0i64; N];
+
+            labels.data[start] = label;
+            open.push(start);
+            while let Some(index) = open.pop() {
+                let p: Vector<N, i32> = self.unindex(index).unwrap();
+                region.area += 1;
+                region.bounds.cover(&p);
+                for (axis, x) in p.values.iter().enumerate() {
+                    sum[axis] += *x as i64;
+                }
+
+                let value = self.get_linear(index);
+                for offset in offsets.iter() {
+                    if let Some(n) = self.index(p + *offset) {
+                        let neighbour = self.get_linear(n);
+                        if labels.data[n] == 0 && include(neighbour) && same(value, neighbour) {
+                            labels.data[n] = label;
+                            open.push(n);
+                        }
+                    }
+                }
+            }
+
+            regions.push(region);
+            sums.push(sum);
+        }
+
+        for (region, sum) in regions.iter_mut().zip(sums) {
+            for (c, s) in region.centroid.values.iter_mut().zip(sum) {
+                *c = (s as f64 / region.area as f64) as f32;
+            }
+        }
+
+        let faces = Connectivity::Face.offsets::<N>();
+        for index in 0..labels.data.len() {
+            let label = labels.data[index];
+            if label == 0 {
+                continue;
+            }
+            let p: Vector<N, i32> = labels.unindex(index).unwrap();
+            let perimeter = faces
+                .iter()
+                .filter(|offset| labels.get(p + **offset) != Some(&label))
+                .count();
+            regions[label as usize - 1].perimeter += perimeter;
+        }
+
+        (labels, regions)
     }
 }
 
-impl<T: Copy> Array3d<T> {
-    pub fn with_dimensions(width: usize, height: usize, depth: usize, default: T) -> Self {
-        Self {
-            data: iter::repeat(default).take(width * height * depth).collect(),
-            dims: [width, height, depth],
-            dim_strides: [1, width, width * height],
+impl<const N: usize, T: Copy + PartialEq> ArrayNd<N, T> {
+    // NOTE: Replaces the region of equal cells containing `start` with `value`, returns the number
+    // of cells changed.
+    pub fn flood_fill(
+        &mut self,
+        start: Vector<N, i32>,
+        connectivity: Connectivity,
+        value: T,
+    ) -> usize {
+        let region = self.flood_region(start, connectivity);
+        for p in region.iter() {
+            self.set(*p, value);
         }
+        region.len()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::vector::V2i32;
+impl<const N: usize, T> ArrayNd<N, T> {
+    // NOTE: Breadth-first distance of every cell to the nearest of `sources`, moving only through
+    // cells for which `passable` holds. Sources themselves are at distance 0 even if they are not
+    // passable, unreachable cells are `None`.
+    pub fn distance_field<F>(
+        &self,
+        sources: &[Vector<N, i32>],
+        connectivity: Connectivity,
+        passable: F,
+    ) -> ArrayNd<N, Option<u32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let offsets = connectivity.offsets::<N>();
+        let mut distances = self.map(|_| None);
+        let mut open = VecDeque::new();
+        for source in sources {
+            if let Some(index) = self.index(*source) {
+                if distances.data[index].is_none() {
+                    distances.data[index] = Some(0);
+                    open.push_back(index);
+                }
+            }
+        }
 
-    use super::*;
+        while let Some(index) = open.pop_front() {
+            let p: Vector<N, i32> = self.unindex(index).unwrap();
+            let distance = distances.data[index].unwrap() + 1;
+            for offset in offsets.iter() {
+                if let Some(n) = self.index(p + *offset) {
+                    if distances.data[n].is_none() && passable(self.get_linear(n)) {
+                        distances.data[n] = Some(distance);
+                        open.push_back(n);
+                    }
+                }
+            }
+        }
 
-    const EXAMPLE: &str = r#"
-467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..
-"#;
+        distances
+    }
 
-    #[test]
-    fn parse_chararray2d() {
-        let map: CharArray2d = EXAMPLE.parse().unwrap();
-        assert_eq!(10, map.width());
+    fn features<F>(&self, is_feature: F) -> Vec<Vector<N, i32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| is_feature(x))
+            .map(|(i, _)| self.unindex(i).unwrap())
+            .collect()
+    }
+
+    // NOTE: Manhattan distance of every cell to the nearest cell for which `is_feature` holds,
+    // `None` everywhere if there are no features.
+    pub fn distance_transform_manhattan<F>(&self, is_feature: F) -> ArrayNd<N, Option<u32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.distance_field(&self.features(is_feature), Connectivity::Face, |_| true)
+    }
+
+    // NOTE: Chebyshev distance of every cell to the nearest cell for which `is_feature` holds.
+    pub fn distance_transform_chebyshev<F>(&self, is_feature: F) -> ArrayNd<N, Option<u32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.distance_field(&self.features(is_feature), Connectivity::Vertex, |_| true)
+    }
+
+    // NOTE: Exact squared Euclidean distance of every cell to the nearest cell for which
+    // `is_feature` holds. Separable lower envelope of parabolas, see Felzenszwalb & Huttenlocher,
+    // "Distance Transforms of Sampled Functions".
+    pub fn distance_transform_euclidean_squared<F>(&self, is_feature: F) -> ArrayNd<N, Option<u64>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut distances = self.map(|x| match is_feature(x) {
+            true => Some(0),
+            false => None,
+        });
+
+        let mut line = vec![];
+        let mut transformed = vec![];
+        for axis in 0..N {
+            let stride = self.dim_strides[axis];
+            let len = self.dims[axis];
+            for start in 0..distances.data.len() {
+                if (start / stride) % len != 0 {
+                    continue;
+                }
+
+                line.clear();
+                line.extend((0..len).map(|i| distances.data[start + i * stride]));
+                transformed.resize(len, None);
+                squared_distance_1d(&line, &mut transformed);
+                for (i, d) in transformed.iter().enumerate() {
+                    distances.data[start + i * stride] = *d;
+                }
+            }
+        }
+
+        distances
+    }
+
+    pub fn distance_transform_euclidean<F>(&self, is_feature: F) -> ArrayNd<N, Option<f32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.distance_transform_euclidean_squared(is_feature)
+            .map(|d| d.map(|d| (d as f64).sqrt() as f32))
+    }
+}
+
+fn squared_distance_1d(f: &[Option<u64>], out: &mut [Option<u64>]) {
+    let height = |q: usize| f[q].unwrap() as f64 + (q * q) as f64;
+    let intersection = |p: usize, q: usize| (height(q) - height(p)) / (2 * (q - p)) as f64;
+
+    // NOTE: `parabolas[k]` is the lowest parabola starting from `boundaries[k]`.
+    let mut parabolas: Vec<usize> = vec![];
+    let mut boundaries: Vec<f64> = vec![];
+    for q in (0..f.len()).filter(|&q| f[q].is_some()) {
+        while let Some(&p) = parabolas.last() {
+            let s = intersection(p, q);
+            if s <= *boundaries.last().unwrap() {
+                parabolas.pop();
+                boundaries.pop();
+            } else {
+                parabolas.push(q);
+                boundaries.push(s);
+                break;
+            }
+        }
+        if parabolas.is_empty() {
+            parabolas.push(q);
+            boundaries.push(f64::NEG_INFINITY);
+        }
+    }
+
+    let mut k = 0;
+    for (q, d) in out.iter_mut().enumerate() {
+        if parabolas.is_empty() {
+            *d = None;
+            continue;
+        }
+        while k + 1 < parabolas.len() && boundaries[k + 1] < q as f64 {
+            k += 1;
+        }
+        let p = parabolas[k];
+        *d = Some((q.abs_diff(p) * q.abs_diff(p)) as u64 + f[p].unwrap());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    // NOTE: All cells within the given Chebyshev distance.
+    Moore(usize),
+    // NOTE: All cells within the given Manhattan distance.
+    VonNeumann(usize),
+}
+
+impl Neighbourhood {
+    pub fn radius(&self) -> usize {
+        match self {
+            Self::Moore(r) | Self::VonNeumann(r) => *r,
+        }
+    }
+
+    pub fn contains<const N: usize>(&self, offset: Vector<N, i32>) -> bool {
+        let r = self.radius() as i32;
+        match self {
+            Self::Moore(_) => offset.values.iter().all(|x| x.abs() <= r),
+            Self::VonNeumann(_) => offset.values.iter().map(|x| x.abs()).sum::<i32>() <= r,
+        }
+    }
+
+    // NOTE: The cell itself is not included.
+    pub fn offsets<const N: usize>(&self) -> Vec<Vector<N, i32>> {
+        let side = 2 * self.radius() + 1;
+        let corner = Vector::all(self.radius() as i32);
+        (0..side.pow(N as u32))
+            .map(|i| {
+                let p: Vector<N, usize> = Vector::all(side).unindex(i).unwrap();
+                let p: Vector<N, i32> = p.try_into().unwrap();
+                p - corner
+            })
+            .filter(|p| *p != Vector::all(0) && self.contains(*p))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Boundary<T> {
+    // NOTE: Cells outside of the grid read as this value.
+    Fixed(T),
+    Wrap,
+    // NOTE: Space is unbounded and filled with this background value. The grid is padded whenever
+    // a non-background cell gets within the neighbourhood radius of its border.
+    Grow(T),
+}
+
+// NOTE: What an automaton rule sees around a cell. Offsets are relative to the cell.
+pub struct CellNeighbourhood<'a, const N: usize, T> {
+    source: &'a ArrayNd<N, T>,
+    centre: Vector<N, i32>,
+    position: Vector<N, i32>,
+    neighbourhood: Neighbourhood,
+    offsets: &'a [Vector<N, i32>],
+}
+
+impl<'a, const N: usize, T> CellNeighbourhood<'a, N, T> {
+    // NOTE: Position of the cell in the grid being stepped.
+    pub fn position(&self) -> Vector<N, i32> {
+        self.position
+    }
+
+    pub fn get(&self, offset: Vector<N, i32>) -> Option<&'a T> {
+        if self.neighbourhood.contains(offset) {
+            self.source.get(self.centre + offset)
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vector<N, i32>, &'a T)> + 'a {
+        let (source, centre) = (self.source, self.centre);
+        self.offsets
+            .iter()
+            .map(move |o| (*o, source.get(centre + *o).unwrap()))
+    }
+
+    pub fn count<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter().filter(|(_, x)| predicate(x)).count()
+    }
+
+    // NOTE: The whole box of side `2 * radius + 1` centred on the cell, including corners a von
+    // Neumann neighbourhood does not contain.
+    pub fn view(&self) -> ArrayView<'a, N, T> {
+        let r = Vector::all(self.neighbourhood.radius() as i32);
+        self.source
+            .window(Aabb::new(self.centre - r, self.centre + r))
+            .unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    // NOTE: First generation that is part of the cycle.
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    pub fn is_steady_state(&self) -> bool {
+        self.period == 1
+    }
+}
+
+pub struct Automaton<const N: usize, T, F> {
+    pub grid: ArrayNd<N, T>,
+    // NOTE: Position of `grid[0]` in the unbounded space, only moves with `Boundary::Grow`.
+    pub origin: Vector<N, i32>,
+    pub generation: usize,
+    neighbourhood: Neighbourhood,
+    boundary: Boundary<T>,
+    rule: F,
+    offsets: Vec<Vector<N, i32>>,
+    // NOTE: Previous generation padded by the neighbourhood radius, the rule reads from here
+    // while the next generation is written straight into `grid`.
+    buffer: ArrayNd<N, T>,
+}
+
+impl<const N: usize, T, F> Automaton<N, T, F>
+where
+    T: Copy + PartialEq,
+    F: FnMut(&T, &CellNeighbourhood<'_, N, T>) -> T,
+{
+    pub fn new(
+        grid: ArrayNd<N, T>,
+        neighbourhood: Neighbourhood,
+        boundary: Boundary<T>,
+        rule: F,
+    ) -> Self {
+        Self {
+            buffer: grid.clone(),
+            grid,
+            origin: Vector::all(0),
+            generation: 0,
+            neighbourhood,
+            boundary,
+            rule,
+            offsets: neighbourhood.offsets(),
+        }
+    }
+
+    fn grow(&mut self, background: T) {
+        let r = self.neighbourhood.radius() as i32;
+        let dims: Vector<N, i32> = Vector::new(self.grid.dims).try_into().unwrap();
+        let near_border = (0..self.grid.data.len())
+            .filter(|i| self.grid.data[*i] != background)
+            .any(|i| {
+                let p: Vector<N, i32> = self.grid.unindex(i).unwrap();
+                p.values
+                    .iter()
+                    .zip(dims.values)
+                    .any(|(x, d)| *x < r || *x >= d - r)
+            });
+
+        if near_border {
+            self.grid = self.grid.padded(r, background);
+            self.origin -= Vector::all(r);
+        }
+    }
+
+    fn fill_buffer(&mut self) {
+        let r = self.neighbourhood.radius();
+        let dims = self.grid.dims.map(|d| d + 2 * r);
+        if self.buffer.dims != dims {
+            self.buffer = ArrayNd::new(dims, self.grid.data[0]);
+        }
+
+        for i in 0..self.buffer.data.len() {
+            let q: Vector<N, i32> = self.buffer.unindex(i).unwrap();
+            let p = q - Vector::all(r as i32);
+            self.buffer.data[i] = match (self.grid.get(p), self.boundary) {
+                (Some(v), _) => *v,
+                (None, Boundary::Fixed(v) | Boundary::Grow(v)) => v,
+                (None, Boundary::Wrap) => *self.grid.get_wrapping(p),
+            };
+        }
+    }
+
+    // NOTE: Advances one generation, returns whether any cell changed.
+    pub fn step(&mut self) -> bool {
+        if let Boundary::Grow(background) = self.boundary {
+            self.grow(background);
+        }
+        self.fill_buffer();
+
+        let r = Vector::all(self.neighbourhood.radius() as i32);
+        let mut changed = false;
+        for i in 0..self.grid.data.len() {
+            let position: Vector<N, i32> = self.grid.unindex(i).unwrap();
+            let cell = CellNeighbourhood {
+                source: &self.buffer,
+                centre: position + r,
+                position,
+                neighbourhood: self.neighbourhood,
+                offsets: &self.offsets,
+            };
+            let old = cell.source.get(cell.centre).unwrap();
+            let new = (self.rule)(old, &cell);
+            changed |= new != *old;
+            self.grid.data[i] = new;
+        }
+
+        self.generation += 1;
+        changed
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+}
+
+impl<const N: usize, T, F> Automaton<N, T, F>
+where
+    T: Copy + Eq + Hash,
+    F: FnMut(&T, &CellNeighbourhood<'_, N, T>) -> T,
+{
+    // NOTE: Steps until a grid repeats, at most `max_generations` times. A steady state is a cycle
+    // of period 1.
+    pub fn run_until_cycle(&mut self, max_generations: usize) -> Option<Cycle> {
+        let mut seen = HashMap::new();
+        seen.insert(self.grid.clone(), self.generation);
+        for _ in 0..max_generations {
+            self.step();
+            if let Some(&start) = seen.get(&self.grid) {
+                return Some(Cycle {
+                    start,
+                    period: self.generation - start,
+                });
+            }
+            seen.insert(self.grid.clone(), self.generation);
+        }
+        None
+    }
+
+    // NOTE: Same result as `run`, but skips whole periods once a cycle is found.
+    pub fn advance(&mut self, generations: usize) {
+        let target = self.generation + generations;
+        if let Some(cycle) = self.run_until_cycle(generations) {
+            let remaining = target - self.generation;
+            self.generation += remaining - remaining % cycle.period;
+            self.run(remaining % cycle.period);
+        }
+    }
+}
+
+impl<const C: usize, T> IterateNeighboursContext for ArrayNd<C, T> {}
+
+impl<const C: usize, T: IterateNeighbours<()> + Copy, U> IterateNeighbours<ArrayNd<C, U>> for T
+where
+    ArrayNd<C, U>: LinearIndex<T>,
+{
+    fn neighbours(&self, _context: &ArrayNd<C, U>) -> Vec<Self> {
+        self.neighbours(&())
+            .into_iter()
+            .filter(|x| _context.is_in_bounds(x))
+            .collect()
+    }
+}
+
+// NOTE: Exploration context for donut maps, neighbours wrap around the borders of the array.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Torus<const N: usize, T>(pub ArrayNd<N, T>);
+
+impl<const N: usize, T> ArrayNd<N, T> {
+    pub fn into_torus(self) -> Torus<N, T> {
+        Torus(self)
+    }
+}
+
+impl<const N: usize, T> Index<Vector<N, i32>> for Torus<N, T> {
+    type Output = T;
+
+    fn index(&self, index: Vector<N, i32>) -> &Self::Output {
+        self.0.get_wrapping(index)
+    }
+}
+
+impl<const N: usize, T> IndexMut<Vector<N, i32>> for Torus<N, T> {
+    fn index_mut(&mut self, index: Vector<N, i32>) -> &mut Self::Output {
+        self.0.get_mut_wrapping(index)
+    }
+}
+
+impl<const N: usize, T> IterateNeighboursContext for Torus<N, T> {}
+
+impl<const N: usize, T> IterateNeighbours<Torus<N, T>> for Vector<N, i32> {
+    fn neighbours(&self, context: &Torus<N, T>) -> Vec<Self> {
+        let p = context.0.wrap(*self);
+        let mut result: Vec<Self> = vec![];
+        // NOTE: Axes of length 1 or 2 would otherwise yield the same cell more than once.
+        for n in p.neighbours(&()).into_iter().map(|n| context.0.wrap(n)) {
+            if n != p && !result.contains(&n) {
+                result.push(n);
+            }
+        }
+        result
+    }
+}
+
+impl<const C: usize, T: Display> Display for ArrayNd<C, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_cells(f, self.dims, |index| self.get_linear(index))
+    }
+}
+
+// NOTE: Shared by `ArrayNd` and the array views: `cell` receives the linear index in storage order
+// (axis 0 fastest) for an array of dimensions `dims`.
+pub(crate) fn fmt_cells<const C: usize, D: Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    dims: [usize; C],
+    cell: impl Fn(usize) -> D,
+) -> std::fmt::Result {
+    let total_size: usize = dims.iter().product();
+    if C > 1 {
+        let mut index = 0;
+        while index < total_size {
+            if C > 2 {
+                let slice: Vector<C, usize> = Vector::new(dims).unindex(index).unwrap();
+                writeln!(f, "Slice = {}", slice)?;
+            }
+
+            for _y in 0..dims[1] {
+                for _x in 0..dims[0] {
+                    write!(f, "{}", cell(index))?;
+                    index += 1;
+                }
+                writeln!(f)?;
+            }
+        }
+    } else {
+        for x in 0..total_size {
+            write!(f, "{}", cell(x))?;
+        }
+    }
+
+    write!(f, "")
+}
+
+// NOTE(lubo): Specific lower dimensional arrays
+
+pub type Array2d<T> = ArrayNd<2, T>;
+pub type Array3d<T> = ArrayNd<3, T>;
+impl<T> Array2d<T> {
+    pub fn width(&self) -> usize {
+        self.dims[0]
+    }
+    pub fn height(&self) -> usize {
+        self.dims[1]
+    }
+}
+impl<T> Array3d<T> {
+    pub fn width(&self) -> usize {
+        self.dims[0]
+    }
+    pub fn height(&self) -> usize {
+        self.dims[1]
+    }
+    pub fn depth(&self) -> usize {
+        self.dims[2]
+    }
+}
+
+pub type CharArray2d = Array2d<char>;
+
+impl FromStr for CharArray2d {
+    type Err = CharArrayParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cursor = std::io::Cursor::new(s);
+        Self::from_read(cursor)
+    }
+}
+
+#[derive(Debug)]
+pub enum CharArrayParseError {
+    InconsistentLineWidth(usize, usize, usize, usize),
+    // NOTE: Line, column and the character the cell mapping rejected.
+    UnknownCharacter(usize, usize, char),
+    // NOTE: Line of a header that could not be parsed or repeats an earlier layer.
+    InvalidLayerHeader(usize),
+    InconsistentLayerHeight(usize, usize, usize, usize),
+    // NOTE: Expected and found number of layers.
+    MissingLayers(usize, usize),
+    // NOTE: Header line of a layer without rows, 0 when there are no layers at all.
+    EmptyLayer(usize),
+    Io(std::io::Error),
+}
+
+impl Display for CharArrayParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CharArrayParseError::InconsistentLineWidth(l1, w1, l2, w2) => write!(f, "Inconsistent line width. On line {l1} the width is {w1}, while on line {l2} the width is {w2}."),
+            CharArrayParseError::UnknownCharacter(l, c, ch) => write!(f, "Unknown character {ch:?} on line {l}, column {c}."),
+            CharArrayParseError::InvalidLayerHeader(l) => write!(f, "Invalid layer header on line {l}."),
+            CharArrayParseError::InconsistentLayerHeight(l1, h1, l2, h2) => write!(f, "Inconsistent layer height. The layer starting on line {l1} has height {h1}, while the one starting on line {l2} has height {h2}."),
+            CharArrayParseError::MissingLayers(expected, found) => write!(f, "Expected {expected} layers, found {found}."),
+            CharArrayParseError::EmptyLayer(l) => write!(f, "Empty layer starting on line {l}."),
+            CharArrayParseError::Io(io) => write!(f, "IO error: {io}"),
+        }
+    }
+}
+
+impl CharArray2d {
+    pub fn from_buffer<R: std::io::Read>(
+        reader: BufReader<R>,
+    ) -> Result<Self, CharArrayParseError> {
+        Self::from_buffer_with(reader, Some, None)
+    }
+
+    pub fn from_read<R: std::io::Read>(reader: R) -> Result<Self, CharArrayParseError> {
+        Self::from_buffer(BufReader::new(reader))
+    }
+}
+
+impl<T: Clone> Array2d<T> {
+    // NOTE: Every character goes through `map`, `None` is reported as an unknown character.
+    // Empty lines are skipped. Without `padding` all lines have to be equally wide, with it
+    // shorter lines are padded at the end to the width of the widest line.
+    pub fn from_buffer_with<R, F>(
+        reader: BufReader<R>,
+        mut map: F,
+        padding: Option<T>,
+    ) -> Result<Self, CharArrayParseError>
+    where
+        R: std::io::Read,
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut first_line: Option<(usize, usize)> = None;
+        let mut rows: Vec<Vec<T>> = vec![];
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => return Err(CharArrayParseError::Io(e)),
+            };
+
+            let mut row = Vec::with_capacity(line.len());
+            for (column, c) in line.chars().enumerate() {
+                match map(c) {
+                    Some(v) => row.push(v),
+                    None => {
+                        return Err(CharArrayParseError::UnknownCharacter(
+                            line_number,
+                            column,
+                            c,
+                        ))
+                    }
+                }
+            }
+            if row.is_empty() {
+                continue;
+            }
+
+            match first_line {
+                None => first_line = Some((line_number, row.len())),
+                Some((first_line_number, width)) => {
+                    if padding.is_none() && width != row.len() {
+                        return Err(CharArrayParseError::InconsistentLineWidth(
+                            first_line_number,
+                            width,
+                            line_number,
+                            row.len(),
+                        ));
+                    }
+                }
+            }
+            rows.push(row);
+        }
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let height = rows.len();
+        let mut data = Vec::with_capacity(width * height);
+        for mut row in rows {
+            if let Some(padding) = &padding {
+                row.resize(width, padding.clone());
+            }
+            data.extend(row);
+        }
+
+        Ok(Self {
+            data,
+            dims: [width, height],
+            dim_strides: [1, width],
+        })
+    }
+
+    pub fn from_str_with<F>(
+        s: &str,
+        map: F,
+        padding: Option<T>,
+    ) -> Result<Self, CharArrayParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        Self::from_buffer_with(BufReader::new(std::io::Cursor::new(s)), map, padding)
+    }
+
+    // NOTE: Example:
+    //   Array2d::from_str_table(map, &[('#', true), ('.', false)], None)
+    pub fn from_str_table(
+        s: &str,
+        table: &[(char, T)],
+        padding: Option<T>,
+    ) -> Result<Self, CharArrayParseError> {
+        Self::from_str_with(
+            s,
+            |c| table.iter().find(|(k, _)| *k == c).map(|(_, v)| v.clone()),
+            padding,
+        )
+    }
+
+    pub fn from_str_try_from(s: &str, padding: Option<T>) -> Result<Self, CharArrayParseError>
+    where
+        T: TryFrom<char>,
+    {
+        Self::from_str_with(s, |c| T::try_from(c).ok(), padding)
+    }
+}
+
+impl<T: Copy> Array2d<T> {
+    pub fn with_dimensions(width: usize, height: usize, default: T) -> Self {
+        Self {
+            data: iter::repeat(default).take(width * height).collect(),
+            dims: [width, height],
+            dim_strides: [1, width],
+        }
+    }
+
+    pub fn shift_n_rows_down(&mut self, n: usize, default: T) {
+        self.data.drain(..self.width() * n);
+        self.data.extend(
+            iter::repeat(default)
+                .take(self.width() * n)
+                .collect::<Vec<T>>(),
+        );
+    }
+}
+
+impl<T: Copy> Array3d<T> {
+    pub fn with_dimensions(width: usize, height: usize, depth: usize, default: T) -> Self {
+        Self {
+            data: iter::repeat(default).take(width * height * depth).collect(),
+            dims: [width, height, depth],
+            dim_strides: [1, width, width * height],
+        }
+    }
+}
+
+// NOTE: Text format for arrays of any dimension, made of 2D layers. A layer starts after a
+// blank line or at a header, either the `Slice = Vector(0, 0, z, ...)` line printed by
+// `Display` or `z=3` for 3D arrays. Layers without a header follow the previous layer along
+// the third axis, so headers are required beyond 3 dimensions. Layers may come in any order,
+// but all of them have to be present.
+//
+// Example:
+//   ..#     z=0
+//   .##     ..#
+//           .##
+//   #..     z=1
+//   ...     #..
+//           ...
+pub type CharArray3d = Array3d<char>;
+
+impl FromStr for CharArray3d {
+    type Err = CharArrayParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_layers_with(s, Some)
+    }
+}
+
+struct Layer<'a> {
+    header_line: usize,
+    coordinates: Vec<usize>,
+    rows: Vec<(usize, &'a str)>,
+}
+
+// NOTE: `None` if `line` is not a header, `Some(None)` if it is a malformed one.
+fn parse_layer_header<const N: usize>(line: &str) -> Option<Option<Vec<usize>>> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("Slice") {
+        let values = rest
+            .trim_start()
+            .strip_prefix('=')
+            .and_then(|x| x.trim().strip_prefix("Vector("))
+            .and_then(|x| x.strip_suffix(')'));
+        let coordinates = values.and_then(|values| {
+            let v: Result<Vec<usize>, _> = values
+                .split(',')
+                .map(|x| x.trim().parse::<usize>())
+                .collect();
+            v.ok()
+        });
+        return Some(
+            coordinates.and_then(|c| match c.len() == N && c[0] == 0 && c[1] == 0 {
+                true => Some(c[2..].to_vec()),
+                false => None,
+            }),
+        );
+    }
+    if let Some(rest) = line.strip_prefix('z') {
+        let rest = rest.trim_start().strip_prefix('=')?;
+        return Some(match (N, rest.trim().parse::<usize>()) {
+            (3, Ok(z)) => Some(vec![z]),
+            _ => None,
+        });
+    }
+    None
+}
+
+impl<const N: usize, T: Clone> ArrayNd<N, T> {
+    pub fn from_layers_with<F>(s: &str, mut map: F) -> Result<Self, CharArrayParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        assert!(N >= 2, "layers are two dimensional");
+
+        let mut layers: Vec<Layer> = vec![];
+        let mut open = false;
+        for (line_number, line) in s.lines().enumerate() {
+            if N > 2 {
+                if let Some(header) = parse_layer_header::<N>(line) {
+                    let coordinates =
+                        header.ok_or(CharArrayParseError::InvalidLayerHeader(line_number))?;
+                    layers.push(Layer {
+                        header_line: line_number,
+                        coordinates,
+                        rows: vec![],
+                    });
+                    open = true;
+                    continue;
+                }
+            }
+            if line.is_empty() {
+                if N > 2 && layers.last().is_some_and(|l| !l.rows.is_empty()) {
+                    open = false;
+                }
+                continue;
+            }
+            if !open {
+                let coordinates = match (N, layers.last()) {
+                    (2, _) | (3, None) => vec![0; N - 2],
+                    (3, Some(previous)) => vec![previous.coordinates[0] + 1],
+                    _ => return Err(CharArrayParseError::InvalidLayerHeader(line_number)),
+                };
+                layers.push(Layer {
+                    header_line: line_number,
+                    coordinates,
+                    rows: vec![],
+                });
+                open = true;
+            }
+            layers.last_mut().unwrap().rows.push((line_number, line));
+        }
+
+        let mut dims = [0; N];
+        let mut first: Option<(usize, usize)> = None;
+        for layer in layers.iter() {
+            if N > 2 && first.is_some_and(|(_, height)| height != layer.rows.len()) {
+                let (line, height) = first.unwrap();
+                return Err(CharArrayParseError::InconsistentLayerHeight(
+                    line,
+                    height,
+                    layer.header_line,
+                    layer.rows.len(),
+                ));
+            }
+            first.get_or_insert((layer.header_line, layer.rows.len()));
+            dims[1] = dims[1].max(layer.rows.len());
+            for (d, c) in dims[2..].iter_mut().zip(layer.coordinates.iter()) {
+                let Some(end) = c.checked_add(1) else {
+                    return Err(CharArrayParseError::InvalidLayerHeader(layer.header_line));
+                };
+                *d = (*d).max(end);
+            }
+        }
+
+        let mut width: Option<(usize, usize)> = None;
+        for layer in layers.iter() {
+            for (line_number, line) in layer.rows.iter() {
+                let line_width = line.chars().count();
+                match width {
+                    None => width = Some((*line_number, line_width)),
+                    Some((first_line, w)) if w != line_width => {
+                        return Err(CharArrayParseError::InconsistentLineWidth(
+                            first_line,
+                            w,
+                            *line_number,
+                            line_width,
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        dims[0] = width.map_or(0, |(_, w)| w);
+        if dims.contains(&0) {
+            let empty = layers.iter().find(|l| l.rows.is_empty());
+            let line = empty.map_or(0, |l| l.header_line);
+            return Err(CharArrayParseError::EmptyLayer(line));
+        }
+        // NOTE: Checked before allocating, so a header with a huge coordinate cannot ask for more
+        // cells than the input holds.
+        let found = layers.len();
+        match dims[2..].iter().try_fold(1usize, |n, d| n.checked_mul(*d)) {
+            Some(expected) if expected <= found => {}
+            Some(expected) => return Err(CharArrayParseError::MissingLayers(expected, found)),
+            None => {
+                let last = layers.iter().max_by_key(|l| &l.coordinates).unwrap();
+                return Err(CharArrayParseError::InvalidLayerHeader(last.header_line));
+            }
+        }
+        let mut cells: Vec<Option<T>> = vec![None; dims.iter().product()];
+
+        let mut dim_strides = [0; N];
+        let mut current_stride = 1;
+        for i in 0..N {
+            dim_strides[i] = current_stride;
+            current_stride *= dims[i];
+        }
+
+        for layer in layers.iter() {
+            let start: usize = layer
+                .coordinates
+                .iter()
+                .zip(&dim_strides[2..])
+                .map(|(c, s)| c * s)
+                .sum();
+            if cells[start].is_some() {
+                return Err(CharArrayParseError::InvalidLayerHeader(layer.header_line));
+            }
+            let mut index = start;
+            for (line_number, line) in layer.rows.iter() {
+                for (column, c) in line.chars().enumerate() {
+                    let v = map(c).ok_or(CharArrayParseError::UnknownCharacter(
+                        *line_number,
+                        column,
+                        c,
+                    ))?;
+                    cells[index] = Some(v);
+                    index += 1;
+                }
+            }
+        }
+
+        let expected: usize = dims[2..].iter().product();
+        if N > 2 && found != expected {
+            return Err(CharArrayParseError::MissingLayers(expected, found));
+        }
+
+        Ok(Self {
+            data: cells.into_iter().map(|c| c.unwrap()).collect(),
+            dims,
+            dim_strides,
+        })
+    }
+}
+
+impl<T: Display> Array3d<T> {
+    // NOTE: The blank line separated variant of the layered format, without headers.
+    pub fn to_layers_string(&self) -> String {
+        let mut result = String::new();
+        for (i, x) in self.data.iter().enumerate() {
+            if i > 0 && i % self.dim_strides[2] == 0 {
+                result.push('\n');
+            }
+            result += &x.to_string();
+            if (i + 1) % self.dims[0] == 0 {
+                result.push('\n');
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vector::{V2i32, V2};
+
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+"#;
+
+    #[test]
+    fn parse_chararray2d() {
+        let map: CharArray2d = EXAMPLE.parse().unwrap();
+        assert_eq!(10, map.width());
         assert_eq!(10, map.height());
         assert_eq!(Some(&'4'), map.get(V2i32::from_xy(0, 0)));
     }
@@ -653,4 +1945,586 @@ line 3+
             panic!();
         }
     }
+
+    #[test]
+    fn connectivity_offsets() {
+        assert_eq!(Connectivity::Face.offsets::<2>().len(), 4);
+        assert_eq!(Connectivity::Edge.offsets::<2>().len(), 8);
+        assert_eq!(Connectivity::Vertex.offsets::<2>().len(), 8);
+        assert_eq!(Connectivity::Face.offsets::<3>().len(), 6);
+        assert_eq!(Connectivity::Edge.offsets::<3>().len(), 18);
+        assert_eq!(Connectivity::Vertex.offsets::<3>().len(), 26);
+    }
+
+    #[test]
+    fn flood_fill_chararray2d() {
+        let mut map: CharArray2d = "..#..\n..#..\n.#...".parse().unwrap();
+        let filled = map.flood_fill(V2i32::from_xy(0, 0), Connectivity::Face, 'o');
+        assert_eq!(filled, 5);
+        assert_eq!(map.get(V2i32::from_xy(3, 0)), Some(&'.'));
+
+        let filled = map.flood_fill(V2i32::from_xy(0, 0), Connectivity::Vertex, '.');
+        assert_eq!(filled, 5);
+        let filled = map.flood_fill(V2i32::from_xy(0, 0), Connectivity::Vertex, 'o');
+        assert_eq!(filled, 12);
+    }
+
+    #[test]
+    fn label_components_regions() {
+        let map: CharArray2d = EXAMPLE.parse().unwrap();
+        let (labels, regions) =
+            map.label_components_by(Connectivity::Vertex, |c| c.is_ascii_digit());
+        assert_eq!(regions.len(), 10);
+        assert_eq!(labels.get(V2i32::from_xy(3, 0)), Some(&0));
+
+        let label = *labels.get(V2i32::from_xy(0, 0)).unwrap();
+        let region = &regions[label as usize - 1];
+        assert_eq!(region.label, label);
+        assert_eq!(region.area, 3);
+        assert_eq!(
+            region.bounds,
+            Aabb::new(V2i32::from_xy(0, 0), V2i32::from_xy(2, 0))
+        );
+        assert_eq!(region.perimeter, 8);
+        assert_eq!(region.centroid, Vector::new([1.0, 0.0]));
+    }
+
+    #[test]
+    fn label_components_3d() {
+        let mut cube = Array3d::with_dimensions(4, 4, 4, 0);
+        cube.set(Vector::new([0, 0, 0]), 1);
+        cube.set(Vector::new([1, 1, 1]), 1);
+        cube.set(Vector::new([3, 3, 3]), 1);
+
+        let (_, regions) = cube.label_components(Connectivity::Face);
+        assert_eq!(regions.len(), 4);
+        let (_, regions) = cube.label_components(Connectivity::Vertex);
+        assert_eq!(regions.len(), 3);
+        let (_, regions) = cube.label_components_by(Connectivity::Vertex, |x| *x == 1);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].area, 2);
+        assert_eq!(regions[0].perimeter, 12);
+        assert_eq!(regions[1].area, 1);
+        assert_eq!(regions[1].perimeter, 6);
+    }
+
+    #[test]
+    fn distance_field_multi_source() {
+        let map: CharArray2d = "...#.\n.#.#.\n.#...".parse().unwrap();
+        let distances = map.distance_field(
+            &[V2i32::from_xy(0, 0), V2i32::from_xy(4, 0)],
+            Connectivity::Face,
+            |c| *c != '#',
+        );
+        assert_eq!(distances.get(V2i32::from_xy(2, 2)), Some(&Some(4)));
+        assert_eq!(distances.get(V2i32::from_xy(3, 2)), Some(&Some(3)));
+        assert_eq!(distances.get(V2i32::from_xy(1, 1)), Some(&None));
+
+        let distances =
+            map.distance_field(&[V2i32::from_xy(0, 0)], Connectivity::Face, |c| *c != '#');
+        assert_eq!(distances.get(V2i32::from_xy(4, 0)), Some(&Some(8)));
+    }
+
+    #[test]
+    fn distance_transforms_single_feature() {
+        let mut grid = Array2d::with_dimensions(7, 5, false);
+        let feature = V2i32::from_xy(2, 3);
+        grid.set(feature, true);
+
+        let manhattan = grid.distance_transform_manhattan(|x| *x);
+        let chebyshev = grid.distance_transform_chebyshev(|x| *x);
+        let euclidean = grid.distance_transform_euclidean_squared(|x| *x);
+        for index in 0..grid.data.len() {
+            let p: V2i32 = grid.unindex(index).unwrap();
+            let d = p - feature;
+            assert_eq!(manhattan[index], Some((d.x().abs() + d.y().abs()) as u32));
+            assert_eq!(chebyshev[index], Some(d.x().abs().max(d.y().abs()) as u32));
+            assert_eq!(euclidean[index], Some(d.inner(d) as u64));
+        }
+    }
+
+    #[test]
+    fn distance_transform_euclidean_brute_force() {
+        let map: CharArray2d = "#.......\n......#.\n........\n..#.....\n.......#"
+            .parse()
+            .unwrap();
+        let features = map.find_all_items::<V2i32>(&'#');
+        let euclidean = map.distance_transform_euclidean_squared(|c| *c == '#');
+        for index in 0..map.data.len() {
+            let p: V2i32 = map.unindex(index).unwrap();
+            let expected = features.iter().map(|f| (*f - p).inner(*f - p) as u64).min();
+            assert_eq!(euclidean[index], expected);
+        }
+
+        let euclidean = map.distance_transform_euclidean(|c| *c == '#');
+        assert_eq!(
+            euclidean.get(V2i32::from_xy(4, 4)),
+            Some(&Some(5.0f32.sqrt()))
+        );
+
+        let empty = map.distance_transform_euclidean(|c| *c == 'x');
+        assert!(empty.iter().all(|d| d.is_none()));
+    }
+
+    #[test]
+    fn iter_block_plane() {
+        let mut cube = Array3d::with_dimensions(3, 4, 5, 0);
+        cube.draw_block([None, Some(2), None], 1);
+        assert_eq!(cube.iter().filter(|x| **x == 1).count(), 15);
+
+        let block: Vec<_> = cube.iter_block([Some(1), Some(2), None]).collect();
+        assert_eq!(block.len(), 5);
+        for (z, (p, x)) in block.into_iter().enumerate() {
+            assert_eq!(p, Vector::new([1, 2, z as i32]));
+            assert_eq!(*x, 1);
+        }
+
+        assert_eq!(cube.iter_block([None, Some(4), None]).count(), 0);
+    }
+
+    #[test]
+    fn iter_block_mut_writes_matching_cells() {
+        let mut cube = Array3d::with_dimensions(3, 3, 3, 0);
+        for (p, x) in cube.iter_block_mut([None, None, Some(1)]) {
+            *x = p.x() + 10 * p.y();
+        }
+        assert_eq!(cube.get(Vector::new([2, 1, 1])), Some(&12));
+        assert_eq!(cube.get(Vector::new([2, 1, 0])), Some(&0));
+        assert_eq!(cube.iter().filter(|x| **x != 0).count(), 8);
+    }
+
+    #[test]
+    fn get_and_set_slice() {
+        let mut cube = Array3d::with_dimensions(2, 3, 4, 0);
+        for (i, x) in cube.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        let plane: Array2d<usize> = cube.get_slice(1, 2);
+        assert_eq!(plane.dims, [2, 4]);
+        assert_eq!(
+            plane.get(V2i32::from_xy(1, 3)),
+            cube.get(Vector::new([1, 2, 3]))
+        );
+
+        let plane = plane.map(|x| x + 100);
+        cube.set_slice(1, 2, &plane);
+        assert_eq!(cube.get(Vector::new([1, 2, 3])), Some(&123));
+        assert_eq!(cube.get(Vector::new([1, 1, 3])), Some(&21));
+
+        let layer: Array2d<usize> = cube.get_slice(2, 0);
+        assert_eq!(layer.data, vec![0, 1, 2, 3, 104, 105]);
+    }
+
+    fn life(cell: &char, n: &CellNeighbourhood<'_, 2, char>) -> char {
+        match (*cell, n.count(|c| *c == '#')) {
+            ('#', 2) | (_, 3) => '#',
+            _ => '.',
+        }
+    }
+
+    #[test]
+    fn neighbourhood_offsets() {
+        assert_eq!(Neighbourhood::Moore(1).offsets::<2>().len(), 8);
+        assert_eq!(Neighbourhood::Moore(2).offsets::<2>().len(), 24);
+        assert_eq!(Neighbourhood::Moore(1).offsets::<3>().len(), 26);
+        assert_eq!(Neighbourhood::VonNeumann(1).offsets::<2>().len(), 4);
+        assert_eq!(Neighbourhood::VonNeumann(2).offsets::<2>().len(), 12);
+        assert_eq!(Neighbourhood::VonNeumann(1).offsets::<3>().len(), 6);
+    }
+
+    #[test]
+    fn automaton_oscillators() {
+        let blinker: CharArray2d = ".....\n..#..\n..#..\n..#..\n.....\n".parse().unwrap();
+        let mut automaton = Automaton::new(
+            blinker.clone(),
+            Neighbourhood::Moore(1),
+            Boundary::Fixed('.'),
+            life,
+        );
+        assert!(automaton.step());
+        assert_eq!(
+            automaton.grid.to_string(),
+            ".....\n.....\n.###.\n.....\n.....\n"
+        );
+        assert_eq!(
+            automaton.run_until_cycle(10),
+            Some(Cycle {
+                start: 1,
+                period: 2
+            })
+        );
+
+        let mut automaton = Automaton::new(
+            blinker.clone(),
+            Neighbourhood::Moore(1),
+            Boundary::Fixed('.'),
+            life,
+        );
+        automaton.advance(1_000_001);
+        assert_eq!(automaton.generation, 1_000_001);
+        assert_eq!(
+            automaton.grid.to_string(),
+            ".....\n.....\n.###.\n.....\n.....\n"
+        );
+
+        let block: CharArray2d = "##\n##\n".parse().unwrap();
+        let mut automaton =
+            Automaton::new(block, Neighbourhood::Moore(1), Boundary::Fixed('.'), life);
+        assert!(!automaton.step());
+        assert!(automaton.run_until_cycle(10).unwrap().is_steady_state());
+    }
+
+    #[test]
+    fn automaton_wrap() {
+        let glider: CharArray2d = ".#...\n..#..\n###..\n.....\n.....\n".parse().unwrap();
+        let mut automaton = Automaton::new(
+            glider.clone(),
+            Neighbourhood::Moore(1),
+            Boundary::Wrap,
+            life,
+        );
+        automaton.run(4);
+        let shifted: Vec<V2i32> = automaton.grid.find_all_items(&'#');
+        let expected: Vec<V2i32> = glider.find_all_items(&'#');
+        assert_eq!(
+            shifted,
+            expected
+                .iter()
+                .map(|p| *p + V2i32::from_xy(1, 1))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            automaton.run_until_cycle(100),
+            Some(Cycle {
+                start: 4,
+                period: 20
+            })
+        );
+    }
+
+    #[test]
+    fn automaton_grow() {
+        let glider: CharArray2d = ".#.\n..#\n###\n".parse().unwrap();
+        let mut automaton = Automaton::new(
+            glider.clone(),
+            Neighbourhood::Moore(1),
+            Boundary::Grow('.'),
+            life,
+        );
+        automaton.run(8);
+        let origin = automaton.origin;
+        let moved: Vec<V2i32> = automaton
+            .grid
+            .find_all_items(&'#')
+            .into_iter()
+            .map(|p: V2i32| p + origin)
+            .collect();
+        let expected: Vec<V2i32> = glider
+            .find_all_items(&'#')
+            .into_iter()
+            .map(|p: V2i32| p + V2i32::from_xy(2, 2))
+            .collect();
+        assert_eq!(moved, expected);
+        assert!(automaton.grid.width() > 3);
+    }
+
+    #[test]
+    fn automaton_von_neumann() {
+        let mut seed = Array2d::with_dimensions(7, 7, false);
+        seed.set(V2i32::from_xy(3, 3), true);
+        let mut automaton = Automaton::new(
+            seed,
+            Neighbourhood::VonNeumann(1),
+            Boundary::Fixed(false),
+            |cell: &bool, n: &CellNeighbourhood<'_, 2, bool>| *cell || n.count(|x| *x) > 0,
+        );
+        automaton.run(2);
+        assert_eq!(automaton.grid.iter().filter(|x| **x).count(), 13);
+        automaton.run(10);
+        assert_eq!(automaton.grid.iter().filter(|x| **x).count(), 49);
+        assert!(automaton.run_until_cycle(5).unwrap().is_steady_state());
+
+        let mut positions = Array2d::with_dimensions(3, 3, 0);
+        let mut automaton = Automaton::new(
+            positions.clone(),
+            Neighbourhood::Moore(1),
+            Boundary::Fixed(-1),
+            |_: &i32, n: &CellNeighbourhood<'_, 2, i32>| {
+                assert_eq!(n.view().dims(), [3, 3]);
+                assert_eq!(n.get(V2i32::from_xy(2, 0)), None);
+                n.position().x() + 10 * n.position().y()
+            },
+        );
+        automaton.step();
+        for (i, x) in positions.iter_mut().enumerate() {
+            *x = (i % 3 + 10 * (i / 3)) as i32;
+        }
+        assert_eq!(automaton.grid, positions);
+    }
+
+    #[test]
+    fn wrapping_access() {
+        let mut a = Array2d::from_slice([3, 2], &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(a.wrap(V2i32::from_xy(-1, 2)), V2i32::from_xy(2, 0));
+        assert_eq!(*a.get_wrapping(V2i32::from_xy(-1, -1)), 5);
+        assert_eq!(*a.get_wrapping(V2i32::from_xy(7, 3)), 4);
+        a.set_wrapping(V2i32::from_xy(3, -2), 9);
+        assert_eq!(a.data[0], 9);
+
+        let torus = a.into_torus();
+        assert_eq!(torus[V2i32::from_xy(-3, 4)], 9);
+        let mut neighbours = V2i32::from_xy(0, 0).neighbours(&torus);
+        neighbours.sort_by_key(|p| (p.y(), p.x()));
+        // NOTE: With a height of 2, up and down are the same cell.
+        assert_eq!(
+            neighbours,
+            vec![
+                V2i32::from_xy(1, 0),
+                V2i32::from_xy(2, 0),
+                V2i32::from_xy(0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_typed() {
+        let walls =
+            Array2d::from_str_table("#.#\n.##\n", &[('#', true), ('.', false)], None).unwrap();
+        assert_eq!(walls.dims, [3, 2]);
+        assert_eq!(walls.data, vec![true, false, true, false, true, true]);
+
+        let digits: Array2d<u8> =
+            Array2d::from_str_with("123\n456\n", |c| c.to_digit(10).map(|d| d as u8), None)
+                .unwrap();
+        assert_eq!(digits.get(V2i32::from_xy(2, 1)), Some(&6));
+
+        let error =
+            Array2d::<u8>::from_str_with("12\n3x\n", |c| c.to_digit(10).map(|d| d as u8), None)
+                .unwrap_err();
+        assert!(matches!(
+            error,
+            CharArrayParseError::UnknownCharacter(1, 1, 'x')
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Unknown character 'x' on line 1, column 1."
+        );
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Tile {
+            Open,
+            Wall,
+        }
+        impl TryFrom<char> for Tile {
+            type Error = ();
+            fn try_from(c: char) -> Result<Self, ()> {
+                match c {
+                    '.' => Ok(Tile::Open),
+                    '#' => Ok(Tile::Wall),
+                    _ => Err(()),
+                }
+            }
+        }
+        let tiles: Array2d<Tile> = Array2d::from_str_try_from("\n.#\n#.\n", None).unwrap();
+        assert_eq!(tiles.dims, [2, 2]);
+        assert_eq!(tiles.get(V2i32::from_xy(1, 0)), Some(&Tile::Wall));
+        assert!(Array2d::<Tile>::from_str_try_from(".#\n#?\n", None).is_err());
+    }
+
+    #[test]
+    fn parse_ragged() {
+        let ragged = "#\n###\n##\n";
+        assert!(matches!(
+            ragged.parse::<CharArray2d>(),
+            Err(CharArrayParseError::InconsistentLineWidth(0, 1, 1, 3))
+        ));
+
+        let padded = CharArray2d::from_str_with(ragged, Some, Some(' ')).unwrap();
+        assert_eq!(padded.dims, [3, 3]);
+        assert_eq!(padded.to_string(), "#  \n###\n## \n");
+    }
+
+    #[test]
+    fn parse_layers() {
+        let blank: CharArray3d = "..#\n.##\n\n#..\n...\n".parse().unwrap();
+        assert_eq!(blank.dims, [3, 2, 2]);
+        assert_eq!(blank.get(Vector::new([2, 0, 0])), Some(&'#'));
+        assert_eq!(blank.get(Vector::new([0, 0, 1])), Some(&'#'));
+        assert_eq!(blank.to_layers_string(), "..#\n.##\n\n#..\n...\n");
+
+        let headers: CharArray3d = "z=1\n#..\n...\nz = 0\n..#\n.##\n".parse().unwrap();
+        assert_eq!(headers, blank);
+
+        let printed = blank.to_string();
+        assert!(printed.starts_with("Slice = Vector(0, 0, 0)\n"));
+        assert_eq!(printed.parse::<CharArray3d>().unwrap(), blank);
+
+        let mut hyper = ArrayNd::<4, u32>::new([3, 2, 2, 3], 0);
+        for (i, x) in hyper.iter_mut().enumerate() {
+            *x = (i % 10) as u32;
+        }
+        let parsed =
+            ArrayNd::<4, u32>::from_layers_with(&hyper.to_string(), |c| c.to_digit(10)).unwrap();
+        assert_eq!(parsed, hyper);
+
+        let flat = Array2d::from_layers_with(EXAMPLE, Some).unwrap();
+        assert_eq!(flat, EXAMPLE.parse::<CharArray2d>().unwrap());
+    }
+
+    #[test]
+    fn parse_layers_errors() {
+        assert!(matches!(
+            "z=0\n..\nz=2\n..\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::MissingLayers(3, 2))
+        ));
+        assert!(matches!(
+            "z=0\n..\nz=0\n..\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::InvalidLayerHeader(2))
+        ));
+        assert!(matches!(
+            "z=x\n..\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::InvalidLayerHeader(0))
+        ));
+        assert!(matches!(
+            "..\n..\n\n..\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::InconsistentLayerHeight(0, 2, 3, 1))
+        ));
+        assert!(matches!(
+            "..\n\n...\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::InconsistentLineWidth(0, 2, 2, 3))
+        ));
+        assert!(matches!(
+            ArrayNd::<4, char>::from_layers_with("..\n..\n", Some),
+            Err(CharArrayParseError::InvalidLayerHeader(0))
+        ));
+        assert!(matches!(
+            "..\nz=1\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::InconsistentLayerHeight(0, 1, 1, 0))
+        ));
+        assert!(matches!(
+            "z=0\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::EmptyLayer(0))
+        ));
+        assert!(matches!(
+            "\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::EmptyLayer(0))
+        ));
+        assert!(matches!(
+            format!("z={}\n..\n", usize::MAX).parse::<CharArray3d>(),
+            Err(CharArrayParseError::InvalidLayerHeader(0))
+        ));
+        assert!(matches!(
+            "z=0\n..\nz=1000000000000\n..\n".parse::<CharArray3d>(),
+            Err(CharArrayParseError::MissingLayers(1000000000001, 2))
+        ));
+        let huge = format!("Slice = Vector(0, 0, {0}, {0})\n..\n", u32::MAX);
+        assert!(matches!(
+            ArrayNd::<4, char>::from_layers_with(&huge, Some),
+            Err(CharArrayParseError::InvalidLayerHeader(0))
+        ));
+    }
+
+    #[test]
+    fn antialiased_lines() {
+        let mut canvas = Array2d::with_dimensions(6, 3, 0.0f32);
+        canvas.draw_line_antialiased(V2::from_xy(0.0, 1.0), V2::from_xy(5.0, 1.0));
+        assert_eq!(canvas.get(V2i32::from_xy(0, 1)), Some(&0.5));
+        assert_eq!(canvas.get(V2i32::from_xy(2, 1)), Some(&1.0));
+        assert_eq!(canvas.get(V2i32::from_xy(5, 1)), Some(&0.5));
+        assert!(canvas
+            .find_all::<_, V2i32>(|x| *x != 0.0)
+            .iter()
+            .all(|p| p.y() == 1));
+
+        // NOTE: Halfway between two rows, both get half of the coverage.
+        let mut canvas = Array2d::with_dimensions(3, 8, 0.0f32);
+        canvas.draw_line_antialiased(V2::from_xy(0.5, 1.0), V2::from_xy(0.5, 6.0));
+        for y in 2..6 {
+            assert_eq!(canvas.get(V2i32::from_xy(0, y)), Some(&0.5));
+            assert_eq!(canvas.get(V2i32::from_xy(1, y)), Some(&0.5));
+            assert_eq!(canvas.get(V2i32::from_xy(2, y)), Some(&0.0));
+        }
+
+        // NOTE: Every column of a shallow line is covered exactly once in total.
+        let mut canvas = Array2d::with_dimensions(10, 6, 0.0f32);
+        let mut coverage = Array2d::with_dimensions(10, 6, 0.0f32);
+        canvas.draw_line_antialiased(V2::from_xy(1.0, 1.0), V2::from_xy(8.0, 4.0));
+        coverage.draw_line_antialiased_with(
+            V2::from_xy(1.0, 1.0),
+            V2::from_xy(8.0, 4.0),
+            |v, c| *v += c,
+        );
+        for x in 2..8 {
+            let column: f32 = (0..6).map(|y| coverage[V2i32::from_xy(x, y)]).sum();
+            assert!((column - 1.0).abs() < 1e-5);
+        }
+        assert!(canvas.iter().all(|x| (0.0..=1.0).contains(x)));
+
+        // NOTE: Clipped at the borders.
+        let mut canvas = Array2d::with_dimensions(4, 4, 0.0f32);
+        canvas.draw_line_antialiased(V2::from_xy(-5.0, -5.0), V2::from_xy(10.0, 10.0));
+        assert_eq!(canvas.get(V2i32::from_xy(3, 3)), Some(&1.0));
+    }
+
+    #[test]
+    fn thick_lines_and_polylines() {
+        let mut canvas = CharArray2d::with_dimensions(9, 7, '.');
+        canvas.draw_thick_line(
+            Line::new(V2i32::from_xy(2, 3), V2i32::from_xy(6, 3)),
+            1.0,
+            '#',
+        );
+        assert_eq!(
+            canvas.to_string(),
+            ".........\n.........\n..#####..\n.#######.\n..#####..\n.........\n.........\n"
+        );
+
+        let mut canvas = CharArray2d::with_dimensions(5, 5, '.');
+        canvas.draw_thick_line(
+            Line::new(V2i32::from_xy(0, 0), V2i32::from_xy(0, 0)),
+            1.5,
+            '#',
+        );
+        assert_eq!(canvas.to_string(), "##...\n##...\n.....\n.....\n.....\n");
+
+        let mut canvas = CharArray2d::with_dimensions(5, 4, '.');
+        canvas.draw_polyline(
+            &[
+                V2i32::from_xy(0, 0),
+                V2i32::from_xy(4, 0),
+                V2i32::from_xy(4, 3),
+                V2i32::from_xy(1, 3),
+            ],
+            '#',
+        );
+        assert_eq!(canvas.to_string(), "#####\n....#\n....#\n.####\n");
+        canvas.draw_polyline(&[V2i32::from_xy(1, 1)], '@');
+        assert_eq!(canvas.to_string(), "#####\n.@..#\n....#\n.####\n");
+
+        let mut canvas = CharArray2d::with_dimensions(7, 5, '.');
+        canvas.draw_thick_polyline(
+            &[
+                V2i32::from_xy(1, 1),
+                V2i32::from_xy(5, 1),
+                V2i32::from_xy(5, 3),
+            ],
+            0.5,
+            '#',
+        );
+        assert_eq!(
+            canvas.to_string(),
+            ".......\n.#####.\n.....#.\n.....#.\n.......\n"
+        );
+
+        let mut canvas = Array2d::with_dimensions(5, 5, 0.0f32);
+        canvas.draw_polyline_antialiased(&[
+            V2i32::from_xy(0, 0),
+            V2i32::from_xy(4, 0),
+            V2i32::from_xy(4, 4),
+        ]);
+        assert_eq!(canvas.get(V2i32::from_xy(2, 0)), Some(&1.0));
+        assert_eq!(canvas.get(V2i32::from_xy(4, 2)), Some(&1.0));
+        assert_eq!(canvas.get(V2i32::from_xy(4, 0)), Some(&0.75));
+    }
 }
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use super::{
+    aabb::Aabb,
+    arraynd::ArrayNd,
+    geometric_traits::{CoverObject, IterateNeighbours, IterateNeighboursContext},
+    linear_index::LinearIndex,
+    modular::ModularDecompose,
+    vector::Vector,
+};
+
+// NOTE: Unbounded dense grid made of fixed-size `ArrayNd` chunks, allocated the first time one of
+// their cells is written. Positions outside of allocated chunks read as `default`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkedGrid<const N: usize, T> {
+    chunks: HashMap<Vector<N, i32>, ArrayNd<N, T>>,
+    chunk_dims: [usize; N],
+    default: T,
+}
+
+impl<const N: usize, T> ChunkedGrid<N, T> {
+    pub fn chunk_dims(&self) -> [usize; N] {
+        self.chunk_dims
+    }
+
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    fn chunk_size(&self) -> Vector<N, i32> {
+        Vector::new(self.chunk_dims).try_into().unwrap()
+    }
+
+    // NOTE: Splits a position into the key of its chunk and the position inside that chunk.
+    pub fn split(&self, p: Vector<N, i32>) -> (Vector<N, i32>, Vector<N, i32>) {
+        p.modular_decompose(self.chunk_size())
+    }
+
+    // NOTE: Global position of the first cell of chunk `key`.
+    pub fn chunk_origin(&self, key: Vector<N, i32>) -> Vector<N, i32> {
+        key.elementwise_binary(self.chunk_size(), |a, b| a * b)
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn chunk(&self, key: Vector<N, i32>) -> Option<&ArrayNd<N, T>> {
+        self.chunks.get(&key)
+    }
+    pub fn chunk_mut(&mut self, key: Vector<N, i32>) -> Option<&mut ArrayNd<N, T>> {
+        self.chunks.get_mut(&key)
+    }
+    pub fn remove_chunk(&mut self, key: Vector<N, i32>) -> Option<ArrayNd<N, T>> {
+        self.chunks.remove(&key)
+    }
+
+    // NOTE: Allocated chunks by key, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (Vector<N, i32>, &ArrayNd<N, T>)> {
+        self.chunks.iter().map(|(k, c)| (*k, c))
+    }
+
+    // NOTE: Every cell of every allocated chunk with its global position, chunk by chunk.
+    pub fn cells(&self) -> impl Iterator<Item = (Vector<N, i32>, &T)> {
+        self.chunks().flat_map(move |(key, chunk)| {
+            let origin = self.chunk_origin(key);
+            chunk.data.iter().enumerate().map(move |(i, v)| {
+                let p: Vector<N, i32> = chunk.unindex(i).unwrap();
+                (origin + p, v)
+            })
+        })
+    }
+
+    pub fn get(&self, p: Vector<N, i32>) -> &T {
+        let (key, local) = self.split(p);
+        match self.chunks.get(&key) {
+            Some(chunk) => chunk.get(local).unwrap(),
+            None => &self.default,
+        }
+    }
+
+    // NOTE: Box covering all allocated chunks, `None` if there are none.
+    pub fn bounds(&self) -> Option<Aabb<N, i32>> {
+        let mut keys = self.chunks.keys();
+        let first = *keys.next()?;
+        let mut result = Aabb::new(first, first);
+        for key in keys {
+            result.cover(key);
+        }
+        Some(Aabb::new(
+            self.chunk_origin(result.min),
+            self.chunk_origin(result.max + Vector::all(1)) - Vector::all(1),
+        ))
+    }
+}
+
+impl<const N: usize, T: Copy> ChunkedGrid<N, T> {
+    pub fn new(chunk_dims: [usize; N], default: T) -> Self {
+        assert!(chunk_dims.iter().all(|d| *d > 0), "chunks can not be empty");
+        Self {
+            chunks: HashMap::new(),
+            chunk_dims,
+            default,
+        }
+    }
+
+    pub fn get_mut(&mut self, p: Vector<N, i32>) -> &mut T {
+        let (key, local) = self.split(p);
+        let (chunk_dims, default) = (self.chunk_dims, self.default);
+        let chunk = self
+            .chunks
+            .entry(key)
+            .or_insert_with(|| ArrayNd::new(chunk_dims, default));
+        chunk.get_mut(local).unwrap()
+    }
+
+    pub fn set(&mut self, p: Vector<N, i32>, v: T) {
+        *self.get_mut(p) = v;
+    }
+
+    // NOTE: Copies out the cells inside `bounds` (inclusive), `array[p]` is `self[p + bounds.min]`.
+    // `None` if `bounds.max` is below `bounds.min` on any axis.
+    pub fn to_arraynd_in(&self, bounds: Aabb<N, i32>) -> Option<ArrayNd<N, T>> {
+        if bounds.dim().values.iter().any(|d| *d < 0) {
+            return None;
+        }
+        let dims = (bounds.dim() + Vector::all(1)).values;
+        let mut result = ArrayNd::new(dims, self.default);
+        for (i, v) in result.data.iter_mut().enumerate() {
+            let p: Vector<N, i32> = Vector::new(dims).unindex(i).unwrap();
+            *v = *self.get(p + bounds.min);
+        }
+        Some(result)
+    }
+}
+
+impl<const N: usize, T: PartialEq> ChunkedGrid<N, T> {
+    // NOTE: Frees the chunks that only contain the default value.
+    pub fn prune(&mut self) {
+        let default = &self.default;
+        self.chunks
+            .retain(|_, chunk| chunk.data.iter().any(|v| v != default));
+    }
+}
+
+impl<const N: usize, T> IterateNeighboursContext for ChunkedGrid<N, T> {}
+
+impl<const N: usize, T> IterateNeighbours<ChunkedGrid<N, T>> for Vector<N, i32> {
+    fn neighbours(&self, _context: &ChunkedGrid<N, T>) -> Vec<Self> {
+        self.neighbours(&())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkedGrid;
+    use crate::{
+        aabb::Aabb2,
+        vector::{V2i32, V3},
+    };
+
+    #[test]
+    fn split_negative() {
+        let grid = ChunkedGrid::new([4, 8], 0);
+        assert_eq!(
+            grid.split(V2i32::from_xy(5, 3)),
+            (V2i32::from_xy(1, 0), V2i32::from_xy(1, 3))
+        );
+        assert_eq!(
+            grid.split(V2i32::from_xy(-1, -8)),
+            (V2i32::from_xy(-1, -1), V2i32::from_xy(3, 0))
+        );
+        assert_eq!(
+            grid.chunk_origin(V2i32::from_xy(-1, 2)),
+            V2i32::from_xy(-4, 16)
+        );
+    }
+
+    #[test]
+    fn get_set() {
+        let mut grid = ChunkedGrid::new([4, 4], '.');
+        assert_eq!(*grid.get(V2i32::from_xy(-100, 7)), '.');
+        assert_eq!(grid.chunk_count(), 0);
+        assert_eq!(grid.bounds(), None);
+
+        grid.set(V2i32::from_xy(-1, -1), '#');
+        grid.set(V2i32::from_xy(-4, -3), '#');
+        grid.set(V2i32::from_xy(9, 0), 'S');
+        assert_eq!(grid.chunk_count(), 2);
+        assert_eq!(*grid.get(V2i32::from_xy(-1, -1)), '#');
+        assert_eq!(*grid.get(V2i32::from_xy(-2, -1)), '.');
+        assert_eq!(*grid.get(V2i32::from_xy(9, 0)), 'S');
+        assert_eq!(
+            grid.bounds(),
+            Some(Aabb2::new(V2i32::from_xy(-4, -4), V2i32::from_xy(11, 3)))
+        );
+
+        let window = grid.to_arraynd_in(Aabb2::new(V2i32::from_xy(-2, -2), V2i32::from_xy(0, -1)));
+        assert_eq!(window.unwrap().to_string(), "...\n.#.\n");
+        let inverted = Aabb2::new(V2i32::from_xy(0, -1), V2i32::from_xy(-2, -2));
+        assert!(grid.to_arraynd_in(inverted).is_none());
+
+        *grid.get_mut(V2i32::from_xy(9, 0)) = '.';
+        grid.prune();
+        assert_eq!(grid.chunk_count(), 1);
+        assert!(grid.chunk(V2i32::from_xy(-1, -1)).is_some());
+    }
+
+    #[test]
+    fn iterate_cells() {
+        let mut grid = ChunkedGrid::new([2, 2, 2], 0);
+        grid.set(V3::from_xyz(-3, 0, 5), 1);
+        grid.set(V3::from_xyz(4, 4, 4), 2);
+
+        let mut cells: Vec<_> = grid.cells().filter(|(_, v)| **v != 0).collect();
+        cells.sort_by_key(|(p, _)| p.values);
+        assert_eq!(
+            cells,
+            vec![(V3::from_xyz(-3, 0, 5), &1), (V3::from_xyz(4, 4, 4), &2)]
+        );
+        assert_eq!(grid.cells().count(), 16);
+
+        let mut keys: Vec<_> = grid.chunks().map(|(k, _)| k).collect();
+        keys.sort_by_key(|k| k.values);
+        assert_eq!(keys, vec![V3::from_xyz(-2, 0, 2), V3::from_xyz(2, 2, 2)]);
+    }
+}
@@ -1,64 +1,18 @@
 use super::{
+    arraynd::{ArrayNd, Torus},
     geometric_traits::{IterateNeighbours, IterateNeighboursContext},
+    math::Zero,
     sketch::Bag,
+    vector::Vector,
 };
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{BinaryHeap, HashMap, HashSet},
     hash::Hash,
+    marker::PhantomData,
+    ops::Add,
 };
 
-// pub struct ExplorationBuilder<P, C, H, N>
-// where
-//     H: Fn(&P) -> i32,
-// {
-//     context: Option<C>,
-//     avoid_identical: Option<HashSet<P>>,
-//     heuristic: Option<H>,
-//     neighbours: Option<N>,
-// }
-//
-// impl<P, C, H, N> ExplorationBuilder<P, C, H, N>
-// where
-//     H: Fn(&P) -> i32,
-// {
-//     pub fn new() -> Self {
-//         Self {
-//             context: None,
-//             avoid_identical: None,
-//             heuristic: None,
-//             neighbours: None,
-//         }
-//     }
-//
-//     pub fn with_context(self, context, extra_data: C) -> Self {
-//         self.context = Some(context);
-//         self
-//     }
-//
-//     pub fn with_neighbours(self, neighbours: N) -> Self {
-//         self.neighbours = Some(neighbours);
-//         self
-//     }
-//
-//     pub fn with_heuristic(self, heuristic: H) -> Self {
-//         self.heuristic = Some(heuristic);
-//         self
-//     }
-//
-//     pub fn avoid_identical(self, context, extra_data: C) -> Self {
-//         self.avoid_identical = Some(HashSet::new());
-//         self
-//     }
-//
-//     pub fn build(self) -> Exploration<P, C> {
-//         let exp = Exploration::<P, C>::new(self.context.unwrap_or(()));
-//         exp
-//     }
-//     // pub fn from_arraynd()
-//     // pub fn with_distance()
-// }
-
 pub enum ExploreSignals {
     ReachedGoal,
     Explore,
@@ -77,6 +31,378 @@ pub trait PointKeyValue {
     fn compare_values(k: &Self::K, a: &Self::V, b: &Self::V) -> Option<Ordering>;
 }
 
+// NOTE: Policy deciding which states are worth visiting again.
+pub trait Dedup<P> {
+    // NOTE: Called when `p` is taken out of the bag, `false` means it should be skipped.
+    fn visit(&mut self, p: &P) -> bool;
+    // NOTE: Called before `p` is put into the bag, `false` means it should be dropped.
+    fn admit(&self, p: &P) -> bool;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeepAll;
+
+impl<P> Dedup<P> for KeepAll {
+    fn visit(&mut self, _p: &P) -> bool {
+        true
+    }
+    fn admit(&self, _p: &P) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AvoidIdentical<P> {
+    pub seen: HashSet<P>,
+}
+
+impl<P> Default for AvoidIdentical<P> {
+    fn default() -> Self {
+        Self {
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl<P: Copy + Hash + Eq> Dedup<P> for AvoidIdentical<P> {
+    fn visit(&mut self, p: &P) -> bool {
+        self.seen.insert(*p)
+    }
+    fn admit(&self, p: &P) -> bool {
+        !self.seen.contains(p)
+    }
+}
+
+// NOTE: See `Exploration::explore_avoid_worse`.
+#[derive(Debug, Clone)]
+pub struct AvoidWorse<P: PointKeyValue> {
+    pub best: HashMap<P::K, P::V>,
+}
+
+impl<P: PointKeyValue> Default for AvoidWorse<P> {
+    fn default() -> Self {
+        Self {
+            best: HashMap::new(),
+        }
+    }
+}
+
+impl<P: PointKeyValue> Dedup<P> for AvoidWorse<P> {
+    fn visit(&mut self, p: &P) -> bool {
+        let k = p.get_key();
+        let v = p.get_value();
+
+        if let Some(&old_v) = self.best.get(&k) {
+            if let Some(ordering) = P::compare_values(&k, &v, &old_v) {
+                match ordering {
+                    Ordering::Less => return false,
+                    Ordering::Equal => (),
+                    Ordering::Greater => {
+                        self.best.insert(k, v);
+                    }
+                }
+            }
+        } else {
+            self.best.insert(k, v);
+        }
+
+        true
+    }
+    fn admit(&self, p: &P) -> bool {
+        let k = p.get_key();
+        let v = p.get_value();
+
+        if let Some(&old_v) = self.best.get(&k) {
+            if P::compare_values(&k, &v, &old_v) == Some(Ordering::Less) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// NOTE: Entry of the open set used by weighted searches. Ordered so that `BinaryHeap` pops the
+// lowest priority first, ties are broken in favour of states that are further from the start.
+struct PriorityEntry<K, P> {
+    priority: K,
+    cost: K,
+    point: P,
+}
+
+impl<K: Ord, P> PartialEq for PriorityEntry<K, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, P> Eq for PriorityEntry<K, P> {}
+
+impl<K: Ord, P> PartialOrd for PriorityEntry<K, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, P> Ord for PriorityEntry<K, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+fn reconstruct_path<P, V>(
+    parents: &HashMap<P, V>,
+    end: P,
+    parent: impl Fn(&V) -> Option<P>,
+) -> Vec<P>
+where
+    P: Copy + Hash + Eq,
+{
+    let mut path = vec![end];
+    let mut at = end;
+    while let Some(p) = parents.get(&at).and_then(&parent) {
+        path.push(p);
+        at = p;
+    }
+    path.reverse();
+    path
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit<P> {
+    pub parent: Option<P>,
+    pub depth: usize,
+}
+
+// NOTE: Record of a finished exploration. Every state that was explored (or that reached the goal)
+// is stored with the predecessor and depth of its first visit, so following the parents always
+// leads back to `start`.
+#[derive(Debug, Clone)]
+pub struct ExplorationResult<P> {
+    pub visited: HashMap<P, Visit<P>>,
+    pub goal: Option<P>,
+    // NOTE: Number of states taken out of the bag, including the skipped ones.
+    pub visits: usize,
+    // NOTE: Number of states whose neighbours were generated.
+    pub expansions: usize,
+}
+
+impl<P: Copy + Hash + Eq> ExplorationResult<P> {
+    fn new() -> Self {
+        Self {
+            visited: HashMap::new(),
+            goal: None,
+            visits: 0,
+            expansions: 0,
+        }
+    }
+
+    pub fn reached_goal(&self) -> bool {
+        self.goal.is_some()
+    }
+
+    pub fn parent(&self, p: &P) -> Option<P> {
+        self.visited.get(p).and_then(|v| v.parent)
+    }
+
+    pub fn depth(&self, p: &P) -> Option<usize> {
+        self.visited.get(p).map(|v| v.depth)
+    }
+
+    // NOTE: Path from `start` to `p`, both included.
+    pub fn path_to(&self, p: &P) -> Option<Vec<P>> {
+        if self.visited.contains_key(p) {
+            Some(reconstruct_path(&self.visited, *p, |v| v.parent))
+        } else {
+            None
+        }
+    }
+
+    // NOTE: Path from `start` to the state that returned `ExploreSignals::ReachedGoal`.
+    pub fn path(&self) -> Option<Vec<P>> {
+        self.goal.and_then(|goal| self.path_to(&goal))
+    }
+}
+
+// NOTE: A bag of states together with the predecessor and depth of every entry. The bag itself
+// only holds states, entries of the same state are told apart by depth and the shallowest one
+// is handed out first.
+struct Frontier<P, B> {
+    bag: B,
+    visits: HashMap<P, Vec<Visit<P>>>,
+}
+
+impl<P: Copy + Hash + Eq, B: Bag<P>> Frontier<P, B> {
+    fn new(start: P) -> Self {
+        let mut result = Self {
+            bag: B::new(),
+            visits: HashMap::new(),
+        };
+        result.put(
+            start,
+            Visit {
+                parent: None,
+                depth: 0,
+            },
+        );
+        result
+    }
+
+    fn put(&mut self, p: P, visit: Visit<P>) {
+        self.bag.put(p);
+        self.visits.entry(p).or_default().push(visit);
+    }
+
+    fn get(&mut self) -> Option<(P, Visit<P>)> {
+        let p = self.bag.get()?;
+        let visits = self.visits.get_mut(&p).unwrap();
+        let (i, _) = visits
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, v)| v.depth)
+            .unwrap();
+        let visit = visits.swap_remove(i);
+        if visits.is_empty() {
+            self.visits.remove(&p);
+        }
+        Some((p, visit))
+    }
+}
+
+fn explore_with<P, S, D, T, N, F, G, B>(
+    start: P,
+    context: &mut S,
+    extra_data: &mut D,
+    mut data: T,
+    mut neighbours: N,
+    mut goal: G,
+    mut filter_neighbours: F,
+) -> ExplorationResult<P>
+where
+    P: Copy + Hash + Eq,
+    N: FnMut(&P, &S) -> Vec<P>,
+    F: FnMut(&P, &P, &mut T, &mut S, &mut D) -> bool,
+    G: FnMut(&P, &mut T, &mut S, &mut D) -> ExploreSignals,
+    B: Bag<P>,
+{
+    let mut result = ExplorationResult::new();
+    let mut open = Frontier::<P, B>::new(start);
+    while let Some((p, visit)) = open.get() {
+        // let mut open = vec![start];
+        // while !open.is_empty() {
+        //     let p = open.pop().unwrap();
+
+        result.visits += 1;
+        match goal(&p, &mut data, context, extra_data) {
+            ExploreSignals::ReachedGoal => {
+                result.visited.entry(p).or_insert(visit);
+                result.goal = Some(p);
+                break;
+            }
+            ExploreSignals::Explore => {
+                result.visited.entry(p).or_insert(visit);
+            }
+            ExploreSignals::Skip => continue,
+        }
+
+        result.expansions += 1;
+        for n in neighbours(&p, context) {
+            if filter_neighbours(&p, &n, &mut data, context, extra_data) {
+                open.put(
+                    n,
+                    Visit {
+                        parent: Some(p),
+                        depth: visit.depth + 1,
+                    },
+                );
+                // open.push(n);
+            }
+        }
+    }
+
+    result
+}
+
+fn astar_with<P, S, D, K, N, F, G, H>(
+    start: P,
+    context: &mut S,
+    extra_data: &mut D,
+    mut neighbours: N,
+    mut goal: G,
+    mut cost: F,
+    mut heuristic: H,
+) -> Option<(K, Vec<P>)>
+where
+    P: Copy + Hash + Eq,
+    K: Copy + Ord + Add<Output = K> + Zero,
+    N: FnMut(&P, &S) -> Vec<P>,
+    F: FnMut(&P, &P, &mut S, &mut D) -> Option<K>,
+    G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    H: FnMut(&P, &mut S, &mut D) -> K,
+{
+    let mut best: HashMap<P, (K, Option<P>)> = HashMap::new();
+    let mut open = BinaryHeap::new();
+
+    best.insert(start, (K::zero(), None));
+    open.push(PriorityEntry {
+        priority: heuristic(&start, context, extra_data),
+        cost: K::zero(),
+        point: start,
+    });
+
+    while let Some(PriorityEntry {
+        cost: g, point: p, ..
+    }) = open.pop()
+    {
+        // NOTE: A cheaper way to `p` was found after this entry was queued.
+        if best.get(&p).is_some_and(|&(best_g, _)| best_g < g) {
+            continue;
+        }
+
+        match goal(&p, context, extra_data) {
+            ExploreSignals::ReachedGoal => {
+                return Some((g, reconstruct_path(&best, p, |&(_, parent)| parent)))
+            }
+            ExploreSignals::Explore => (),
+            ExploreSignals::Skip => continue,
+        }
+
+        for n in neighbours(&p, context) {
+            if let Some(c) = cost(&p, &n, context, extra_data) {
+                let n_g = g + c;
+                if !best.get(&n).is_some_and(|&(old_g, _)| old_g <= n_g) {
+                    best.insert(n, (n_g, Some(p)));
+                    open.push(PriorityEntry {
+                        priority: n_g + heuristic(&n, context, extra_data),
+                        cost: n_g,
+                        point: n,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+enum DepthLimited {
+    Found,
+    // NOTE: Some states were not explored because of the depth limit.
+    CutOff,
+    Exhausted,
+}
+
+struct IdaStar<'a, K, F, G, H> {
+    goal: &'a mut G,
+    cost: &'a mut F,
+    heuristic: &'a mut H,
+    bound: K,
+    next_bound: Option<K>,
+}
+
 #[derive(Debug)]
 pub struct Exploration<P: IterateNeighbours<S>, S: IterateNeighboursContext, D> {
     pub context: S,
@@ -96,146 +422,1477 @@ where
         }
     }
 
-    pub fn explore<F, G, B: Bag<P>>(&mut self, start: P, mut goal: G, mut filter_neighbours: F)
+    pub fn explore<F, G, B: Bag<P>>(&mut self, start: P, goal: G, filter_neighbours: F)
     where
         F: FnMut(&P, &P, &mut S, &mut D) -> bool,
         G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
     {
-        self.explore_advanced::<_, _, _, B>(
-            start,
-            (),
-            |p, _data, context, extra_data| goal(p, context, extra_data),
-            |p, n, _data, context, extra_data| filter_neighbours(p, n, context, extra_data),
-        )
+        self.explore_traced::<_, _, B>(start, goal, filter_neighbours);
+    }
+
+    // NOTE: Same as `explore`, also returns the predecessor and depth of every visited state.
+    pub fn explore_traced<F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        goal: G,
+        filter_neighbours: F,
+    ) -> ExplorationResult<P>
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_dedup::<_, _, _, B>(start, KeepAll, goal, filter_neighbours)
     }
 
     // NOTE(lubo): Uses a hashset to avoid identical states
     pub fn explore_avoid_identical<F, G, B: Bag<P>>(
         &mut self,
         start: P,
-        mut goal: G,
-        mut filter_neighbours: F,
+        goal: G,
+        filter_neighbours: F,
     ) where
         F: FnMut(&P, &P, &mut S, &mut D) -> bool,
         G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
     {
-        self.explore_advanced::<_, _, _, B>(
-            start,
-            HashSet::new(),
-            |p, data, context, extra_data| {
-                if data.contains(p) {
-                    ExploreSignals::Skip
-                } else {
-                    data.insert(*p);
-                    goal(p, context, extra_data)
-                }
-            },
-            |p, n, data, context, extra_data| {
-                !data.contains(n) && filter_neighbours(p, n, context, extra_data)
-            },
-        )
+        self.explore_avoid_identical_traced::<_, _, B>(start, goal, filter_neighbours);
     }
 
-    pub fn explore_advanced<T, F, G, B: Bag<P>>(
+    pub fn explore_avoid_identical_traced<F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        goal: G,
+        filter_neighbours: F,
+    ) -> ExplorationResult<P>
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_dedup::<_, _, _, B>(start, AvoidIdentical::default(), goal, filter_neighbours)
+    }
+
+    // NOTE: Breadth-first search growing from both `start` and `end` until the two meet, which
+    // visits far fewer states than a plain breadth-first search on large state spaces.
+    // `reverse_neighbours(p, context)` has to return the states that have `p` as a neighbour.
+    //
+    // `goal` is called for every state reached from `start` and follows the usual contract,
+    // `ExploreSignals::ReachedGoal` ends the search early with the path to that state.
+    // Returns the shortest path from `start` to `end`, both included.
+    pub fn explore_bidirectional<F, G, R>(
         &mut self,
         start: P,
-        mut data: T,
+        end: P,
         mut goal: G,
         mut filter_neighbours: F,
-    ) where
-        F: FnMut(&P, &P, &mut T, &mut S, &mut D) -> bool,
-        G: FnMut(&P, &mut T, &mut S, &mut D) -> ExploreSignals,
+        mut reverse_neighbours: R,
+    ) -> Option<Vec<P>>
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+        R: FnMut(&P, &S) -> Vec<P>,
     {
-        let mut open = B::new();
-        open.put(start);
-        while !open.is_empty() {
-            let p = open.get().unwrap();
-            // let mut open = vec![start];
-            // while !open.is_empty() {
-            //     let p = open.pop().unwrap();
+        let root = Visit {
+            parent: None,
+            depth: 0,
+        };
+        let mut forward = HashMap::from([(start, root)]);
+        let mut backward = HashMap::from([(end, root)]);
+        let mut forward_layer = vec![start];
+        let mut backward_layer = vec![end];
 
-            match goal(&p, &mut data, &mut self.context, &mut self.extra_data) {
-                ExploreSignals::ReachedGoal => break,
-                ExploreSignals::Explore => (),
-                ExploreSignals::Skip => continue,
+        let join = |forward: &HashMap<P, Visit<P>>, backward: &HashMap<P, Visit<P>>, at: P| {
+            let mut path = reconstruct_path(forward, at, |v| v.parent);
+            let mut at = at;
+            while let Some(next) = backward.get(&at).and_then(|v| v.parent) {
+                path.push(next);
+                at = next;
             }
+            path
+        };
+
+        if start == end {
+            return Some(vec![start]);
+        }
+
+        while !forward_layer.is_empty() && !backward_layer.is_empty() {
+            // NOTE: All meetings of a layer have to be considered, the states seen from the other
+            // side are not necessarily at the same depth.
+            let mut meeting: Option<(usize, P)> = None;
 
-            for n in p.neighbours(&self.context) {
-                if filter_neighbours(&p, &n, &mut data, &mut self.context, &mut self.extra_data) {
-                    open.put(n);
-                    // open.push(n);
+            if forward_layer.len() <= backward_layer.len() {
+                let mut next_layer = vec![];
+                for p in forward_layer {
+                    match goal(&p, &mut self.context, &mut self.extra_data) {
+                        ExploreSignals::ReachedGoal => {
+                            return Some(reconstruct_path(&forward, p, |v| v.parent))
+                        }
+                        ExploreSignals::Explore => (),
+                        ExploreSignals::Skip => continue,
+                    }
+                    let depth = forward[&p].depth + 1;
+                    for n in p.neighbours(&self.context) {
+                        if forward.contains_key(&n)
+                            || !filter_neighbours(&p, &n, &mut self.context, &mut self.extra_data)
+                        {
+                            continue;
+                        }
+                        forward.insert(
+                            n,
+                            Visit {
+                                parent: Some(p),
+                                depth,
+                            },
+                        );
+                        if let Some(other) = backward.get(&n) {
+                            if !meeting.is_some_and(|(best, _)| best <= other.depth) {
+                                meeting = Some((other.depth, n));
+                            }
+                        }
+                        next_layer.push(n);
+                    }
+                }
+                forward_layer = next_layer;
+            } else {
+                let mut next_layer = vec![];
+                for p in backward_layer {
+                    let depth = backward[&p].depth + 1;
+                    for n in reverse_neighbours(&p, &self.context) {
+                        if backward.contains_key(&n)
+                            || !filter_neighbours(&n, &p, &mut self.context, &mut self.extra_data)
+                        {
+                            continue;
+                        }
+                        backward.insert(
+                            n,
+                            Visit {
+                                parent: Some(p),
+                                depth,
+                            },
+                        );
+                        if let Some(other) = forward.get(&n) {
+                            if !meeting.is_some_and(|(best, _)| best <= other.depth) {
+                                meeting = Some((other.depth, n));
+                            }
+                        }
+                        next_layer.push(n);
+                    }
                 }
+                backward_layer = next_layer;
+            }
+
+            if let Some((_, meeting)) = meeting {
+                return Some(join(&forward, &backward, meeting));
             }
         }
+
+        None
     }
-}
 
-impl<P, S, D> Exploration<P, S, D>
-where
-    P: IterateNeighbours<S> + PointKeyValue,
-    P: Clone + Copy + Hash + Eq,
-    S: IterateNeighboursContext,
-{
-    // NOTE(lubo): Uses a hashmap that tracks the best 'value: V' for reach explored 'key: K'
-    // 'V', 'K' and 'P::compare_values(a: &V, b &V)' need to be defined by user.
-    //
-    // For example, imagine you encounter 'point: P', which has 'key: K' defined as position, and 'value: V' as health.
-    // We can drop this state if we previously encountered a state with the same position ('key: K') and more health ('value: V').
-    //
-    // In pseudocode:
-    //
-    // struct Point
-    //   position: (i32,i32)
-    //   health: i32
-    //
-    // impl PointKeyValue for Point
-    //   get_key() => self.position
-    //   get_value() => self.health
-    //   Point::compare_values(a: &V, b &V) => Some(a < b)
-    pub fn explore_avoid_worse<F, G, B: Bag<P>>(
+    // NOTE: Iterative deepening depth-first search, needs memory only for the current path.
+    // States already on the current path are not revisited. Returns the shortest path (of at
+    // most `max_depth` steps) to the first state for which `goal` returned
+    // `ExploreSignals::ReachedGoal`.
+    pub fn explore_iddfs<F, G>(
         &mut self,
         start: P,
+        max_depth: usize,
         mut goal: G,
         mut filter_neighbours: F,
-    ) where
+    ) -> Option<Vec<P>>
+    where
         F: FnMut(&P, &P, &mut S, &mut D) -> bool,
         G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
     {
-        self.explore_advanced::<_, _, _, B>(
-            start,
-            HashMap::<<P as PointKeyValue>::K, <P as PointKeyValue>::V>::new(),
-            |p, data, context, extra_data| {
-                let k = p.get_key();
-                let v = p.get_value();
-
-                if let Some(&old_v) = data.get(&k) {
-                    if let Some(ordering) = P::compare_values(&k, &v, &old_v) {
-                        match ordering {
-                            Ordering::Less => return ExploreSignals::Skip,
-                            Ordering::Equal => (),
-                            Ordering::Greater => {
-                                data.insert(k, v);
-                            }
-                        }
-                    }
-                } else {
-                    data.insert(k, v);
-                }
+        let mut path = vec![start];
+        for limit in 0..=max_depth {
+            match self.depth_limited(&mut path, limit, &mut goal, &mut filter_neighbours) {
+                DepthLimited::Found => return Some(path),
+                DepthLimited::CutOff => (),
+                DepthLimited::Exhausted => return None,
+            }
+        }
+        None
+    }
 
-                goal(p, context, extra_data)
-            },
-            |p, n, data, context, extra_data| {
-                let k = n.get_key();
-                let v = n.get_value();
+    fn depth_limited<F, G>(
+        &mut self,
+        path: &mut Vec<P>,
+        limit: usize,
+        goal: &mut G,
+        filter_neighbours: &mut F,
+    ) -> DepthLimited
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        let p = *path.last().unwrap();
+        match goal(&p, &mut self.context, &mut self.extra_data) {
+            ExploreSignals::ReachedGoal => return DepthLimited::Found,
+            ExploreSignals::Explore => (),
+            ExploreSignals::Skip => return DepthLimited::Exhausted,
+        }
 
-                if let Some(&old_v) = data.get(&k) {
-                    if P::compare_values(&k, &v, &old_v) == Some(Ordering::Less) {
-                        return false;
-                    }
+        let mut result = DepthLimited::Exhausted;
+        for n in p.neighbours(&self.context) {
+            if path.contains(&n)
+                || !filter_neighbours(&p, &n, &mut self.context, &mut self.extra_data)
+            {
+                continue;
+            }
+            if path.len() > limit {
+                result = DepthLimited::CutOff;
+                break;
+            }
+
+            path.push(n);
+            match self.depth_limited(path, limit, goal, filter_neighbours) {
+                DepthLimited::Found => return DepthLimited::Found,
+                DepthLimited::CutOff => result = DepthLimited::CutOff,
+                DepthLimited::Exhausted => (),
+            }
+            path.pop();
+        }
+        result
+    }
+
+    // NOTE: Iterative deepening A*, the linear memory counterpart of `explore_astar`. Same
+    // requirements on `cost` and `heuristic` apply, states already on the current path are not
+    // revisited.
+    pub fn explore_idastar<K, F, G, H>(
+        &mut self,
+        start: P,
+        mut goal: G,
+        mut cost: F,
+        mut heuristic: H,
+    ) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S, &mut D) -> Option<K>,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+        H: FnMut(&P, &mut S, &mut D) -> K,
+    {
+        let mut path = vec![start];
+        let mut bound = heuristic(&start, &mut self.context, &mut self.extra_data);
+        loop {
+            let mut search = IdaStar {
+                goal: &mut goal,
+                cost: &mut cost,
+                heuristic: &mut heuristic,
+                bound,
+                next_bound: None,
+            };
+            if let Some(total) = self.bounded(&mut path, K::zero(), &mut search) {
+                return Some((total, path));
+            }
+            bound = search.next_bound?;
+        }
+    }
+
+    fn bounded<K, F, G, H>(
+        &mut self,
+        path: &mut Vec<P>,
+        g: K,
+        search: &mut IdaStar<'_, K, F, G, H>,
+    ) -> Option<K>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S, &mut D) -> Option<K>,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+        H: FnMut(&P, &mut S, &mut D) -> K,
+    {
+        let p = *path.last().unwrap();
+        let f = g + (search.heuristic)(&p, &mut self.context, &mut self.extra_data);
+        if f > search.bound {
+            search.next_bound = Some(search.next_bound.map_or(f, |next| next.min(f)));
+            return None;
+        }
+
+        match (search.goal)(&p, &mut self.context, &mut self.extra_data) {
+            ExploreSignals::ReachedGoal => return Some(g),
+            ExploreSignals::Explore => (),
+            ExploreSignals::Skip => return None,
+        }
+
+        for n in p.neighbours(&self.context) {
+            if path.contains(&n) {
+                continue;
+            }
+            if let Some(c) = (search.cost)(&p, &n, &mut self.context, &mut self.extra_data) {
+                path.push(n);
+                if let Some(total) = self.bounded(path, g + c, search) {
+                    return Some(total);
                 }
+                path.pop();
+            }
+        }
+        None
+    }
 
-                filter_neighbours(p, n, context, extra_data)
-            },
-        )
+    pub fn explore_dedup<X, F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        dedup: X,
+        mut goal: G,
+        mut filter_neighbours: F,
+    ) -> ExplorationResult<P>
+    where
+        X: Dedup<P>,
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_advanced_traced::<_, _, _, B>(
+            start,
+            dedup,
+            |p, dedup, context, extra_data| {
+                if dedup.visit(p) {
+                    goal(p, context, extra_data)
+                } else {
+                    ExploreSignals::Skip
+                }
+            },
+            |p, n, dedup, context, extra_data| {
+                dedup.admit(n) && filter_neighbours(p, n, context, extra_data)
+            },
+        )
+    }
+
+    pub fn explore_advanced<T, F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        data: T,
+        goal: G,
+        filter_neighbours: F,
+    ) where
+        F: FnMut(&P, &P, &mut T, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut T, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_advanced_traced::<_, _, _, B>(start, data, goal, filter_neighbours);
+    }
+
+    pub fn explore_advanced_traced<T, F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        data: T,
+        goal: G,
+        filter_neighbours: F,
+    ) -> ExplorationResult<P>
+    where
+        F: FnMut(&P, &P, &mut T, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut T, &mut S, &mut D) -> ExploreSignals,
+    {
+        explore_with::<_, _, _, _, _, _, _, B>(
+            start,
+            &mut self.context,
+            &mut self.extra_data,
+            data,
+            |p, context| p.neighbours(context),
+            goal,
+            filter_neighbours,
+        )
+    }
+
+    // NOTE: Lazy alternative to `explore`, yields `(state, depth, parent)` in the order given by
+    // the bag. See `ExplorationIter` for the pruning adaptors.
+    pub fn iter<B: Bag<P>>(&self, start: P) -> ExplorationIter<'_, P, S, B> {
+        ExplorationCursor::new(start).iter(&self.context)
+    }
+
+    // NOTE: Uniform-cost search. `cost` returns the price of stepping from the first state to the
+    // second one, or `None` if the step is not allowed. Costs must not be negative.
+    //
+    // Returns the cost and the path (including both `start` and the goal) to the first state
+    // for which `goal` returned `ExploreSignals::ReachedGoal`.
+    pub fn explore_dijkstra<K, F, G>(&mut self, start: P, goal: G, cost: F) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S, &mut D) -> Option<K>,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_astar(start, goal, cost, |_, _, _| K::zero())
+    }
+
+    // NOTE: Same as `explore_dijkstra`, states are additionally prioritized by `heuristic`, an
+    // estimate of the remaining cost to the goal. The heuristic has to be admissible (it never
+    // overestimates) for the returned path to be the cheapest one.
+    pub fn explore_astar<K, F, G, H>(
+        &mut self,
+        start: P,
+        goal: G,
+        cost: F,
+        heuristic: H,
+    ) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S, &mut D) -> Option<K>,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+        H: FnMut(&P, &mut S, &mut D) -> K,
+    {
+        astar_with(
+            start,
+            &mut self.context,
+            &mut self.extra_data,
+            |p, context| p.neighbours(context),
+            goal,
+            cost,
+            heuristic,
+        )
+    }
+}
+
+impl<P, S, D> Exploration<P, S, D>
+where
+    P: IterateNeighbours<S> + PointKeyValue,
+    P: Clone + Copy + Hash + Eq,
+    S: IterateNeighboursContext,
+{
+    // NOTE(lubo): Uses a hashmap that tracks the best 'value: V' for reach explored 'key: K'
+    // 'V', 'K' and 'P::compare_values(a: &V, b &V)' need to be defined by user.
+    //
+    // For example, imagine you encounter 'point: P', which has 'key: K' defined as position, and 'value: V' as health.
+    // We can drop this state if we previously encountered a state with the same position ('key: K') and more health ('value: V').
+    //
+    // In pseudocode:
+    //
+    // struct Point
+    //   position: (i32,i32)
+    //   health: i32
+    //
+    // impl PointKeyValue for Point
+    //   get_key() => self.position
+    //   get_value() => self.health
+    //   Point::compare_values(a: &V, b &V) => Some(a < b)
+    pub fn explore_avoid_worse<F, G, B: Bag<P>>(&mut self, start: P, goal: G, filter_neighbours: F)
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_avoid_worse_traced::<_, _, B>(start, goal, filter_neighbours);
+    }
+
+    pub fn explore_avoid_worse_traced<F, G, B: Bag<P>>(
+        &mut self,
+        start: P,
+        goal: G,
+        filter_neighbours: F,
+    ) -> ExplorationResult<P>
+    where
+        F: FnMut(&P, &P, &mut S, &mut D) -> bool,
+        G: FnMut(&P, &mut S, &mut D) -> ExploreSignals,
+    {
+        self.explore_dedup::<_, _, _, B>(start, AvoidWorse::default(), goal, filter_neighbours)
+    }
+}
+
+pub trait Neighbours<P, S> {
+    fn neighbours(&mut self, p: &P, context: &S) -> Vec<P>;
+}
+
+impl<P, S, F> Neighbours<P, S> for F
+where
+    F: FnMut(&P, &S) -> Vec<P>,
+{
+    fn neighbours(&mut self, p: &P, context: &S) -> Vec<P> {
+        self(p, context)
+    }
+}
+
+// NOTE: Neighbours given by the `IterateNeighbours` implementation of the state.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UseIterateNeighbours;
+
+impl<P, S> Neighbours<P, S> for UseIterateNeighbours
+where
+    P: IterateNeighbours<S>,
+    S: IterateNeighboursContext,
+{
+    fn neighbours(&mut self, p: &P, context: &S) -> Vec<P> {
+        p.neighbours(context)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FilteredNeighbours<N, F> {
+    neighbours: N,
+    filter: F,
+}
+
+impl<P, S, N, F> Neighbours<P, S> for FilteredNeighbours<N, F>
+where
+    N: Neighbours<P, S>,
+    F: FnMut(&P, &P, &S) -> bool,
+{
+    fn neighbours(&mut self, p: &P, context: &S) -> Vec<P> {
+        let mut neighbours = self.neighbours.neighbours(p, context);
+        neighbours.retain(|n| (self.filter)(p, n, context));
+        neighbours
+    }
+}
+
+pub trait Heuristic<P, S, K> {
+    fn estimate(&mut self, p: &P, context: &S) -> K;
+}
+
+impl<P, S, K, F> Heuristic<P, S, K> for F
+where
+    F: FnMut(&P, &S) -> K,
+{
+    fn estimate(&mut self, p: &P, context: &S) -> K {
+        self(p, context)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoHeuristic;
+
+impl<P, S, K: Zero> Heuristic<P, S, K> for NoHeuristic {
+    fn estimate(&mut self, _p: &P, _context: &S) -> K {
+        K::zero()
+    }
+}
+
+// NOTE: Assembles an exploration out of parts, without requiring `IterateNeighbours` for the
+// states. The dedup policy is chosen by type, a fresh one is created for every run.
+//
+// Example:
+//   ExplorationBuilder::from_arraynd(map)
+//       .filter_neighbours(|_, n, map| map[*n] != '#')
+//       .with_heuristic(|p, _| p.manhattan_distance(&end))
+//       .avoid_identical()
+//       .build()
+//       .astar(start, goal, cost)
+#[derive(Debug, Clone)]
+pub struct ExplorationBuilder<P, S, N, H, X> {
+    context: S,
+    neighbours: N,
+    heuristic: H,
+    phantom: PhantomData<(P, X)>,
+}
+
+impl<P> ExplorationBuilder<P, (), UseIterateNeighbours, NoHeuristic, KeepAll> {
+    pub fn new() -> Self {
+        Self {
+            context: (),
+            neighbours: UseIterateNeighbours,
+            heuristic: NoHeuristic,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P> Default for ExplorationBuilder<P, (), UseIterateNeighbours, NoHeuristic, KeepAll> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const C: usize, T>
+    ExplorationBuilder<Vector<C, i32>, ArrayNd<C, T>, UseIterateNeighbours, NoHeuristic, KeepAll>
+{
+    // NOTE: Explores the cells of `array`, stepping along the axes without leaving its bounds.
+    pub fn from_arraynd(array: ArrayNd<C, T>) -> Self {
+        ExplorationBuilder::new().with_context(array)
+    }
+}
+
+impl<const C: usize, T>
+    ExplorationBuilder<Vector<C, i32>, Torus<C, T>, UseIterateNeighbours, NoHeuristic, KeepAll>
+{
+    // NOTE: Like `from_arraynd`, but stepping over a border continues on the opposite side.
+    pub fn from_torus(array: ArrayNd<C, T>) -> Self {
+        ExplorationBuilder::new().with_context(array.into_torus())
+    }
+}
+
+impl<P, S, N, H, X> ExplorationBuilder<P, S, N, H, X> {
+    pub fn with_context<S2>(self, context: S2) -> ExplorationBuilder<P, S2, N, H, X> {
+        ExplorationBuilder {
+            context,
+            neighbours: self.neighbours,
+            heuristic: self.heuristic,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_neighbours<N2>(self, neighbours: N2) -> ExplorationBuilder<P, S, N2, H, X>
+    where
+        N2: FnMut(&P, &S) -> Vec<P>,
+    {
+        ExplorationBuilder {
+            context: self.context,
+            neighbours,
+            heuristic: self.heuristic,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn filter_neighbours<F>(
+        self,
+        filter: F,
+    ) -> ExplorationBuilder<P, S, FilteredNeighbours<N, F>, H, X>
+    where
+        F: FnMut(&P, &P, &S) -> bool,
+    {
+        ExplorationBuilder {
+            context: self.context,
+            neighbours: FilteredNeighbours {
+                neighbours: self.neighbours,
+                filter,
+            },
+            heuristic: self.heuristic,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_heuristic<H2>(self, heuristic: H2) -> ExplorationBuilder<P, S, N, H2, X> {
+        ExplorationBuilder {
+            context: self.context,
+            neighbours: self.neighbours,
+            heuristic,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_dedup<X2: Dedup<P> + Default>(self) -> ExplorationBuilder<P, S, N, H, X2> {
+        ExplorationBuilder {
+            context: self.context,
+            neighbours: self.neighbours,
+            heuristic: self.heuristic,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn keep_all(self) -> ExplorationBuilder<P, S, N, H, KeepAll> {
+        self.with_dedup()
+    }
+
+    pub fn avoid_identical(self) -> ExplorationBuilder<P, S, N, H, AvoidIdentical<P>>
+    where
+        P: Copy + Hash + Eq,
+    {
+        self.with_dedup()
+    }
+
+    pub fn avoid_worse(self) -> ExplorationBuilder<P, S, N, H, AvoidWorse<P>>
+    where
+        P: PointKeyValue,
+    {
+        self.with_dedup()
+    }
+
+    pub fn build(self) -> CustomExploration<P, S, N, H, X> {
+        CustomExploration {
+            context: self.context,
+            neighbours: self.neighbours,
+            heuristic: self.heuristic,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomExploration<P, S, N, H, X> {
+    pub context: S,
+    neighbours: N,
+    heuristic: H,
+    phantom: PhantomData<(P, X)>,
+}
+
+impl<P, S, N, H, X> CustomExploration<P, S, N, H, X>
+where
+    P: Copy + Hash + Eq,
+    N: Neighbours<P, S>,
+    X: Dedup<P> + Default,
+{
+    pub fn explore<G, B: Bag<P>>(&mut self, start: P, mut goal: G) -> ExplorationResult<P>
+    where
+        G: FnMut(&P, &mut S) -> ExploreSignals,
+    {
+        let neighbours = &mut self.neighbours;
+        explore_with::<_, _, _, _, _, _, _, B>(
+            start,
+            &mut self.context,
+            &mut (),
+            X::default(),
+            |p, context| neighbours.neighbours(p, context),
+            |p, dedup, context, _| {
+                if dedup.visit(p) {
+                    goal(p, context)
+                } else {
+                    ExploreSignals::Skip
+                }
+            },
+            |_, n, dedup, _, _| dedup.admit(n),
+        )
+    }
+
+    pub fn dijkstra<K, F, G>(&mut self, start: P, goal: G, cost: F) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S) -> Option<K>,
+        G: FnMut(&P, &mut S) -> ExploreSignals,
+    {
+        Self::weighted(
+            &mut self.context,
+            &mut self.neighbours,
+            start,
+            goal,
+            cost,
+            |_, _| K::zero(),
+        )
+    }
+
+    // NOTE: Uses the heuristic given to the builder, without one this is the same as `dijkstra`.
+    pub fn astar<K, F, G>(&mut self, start: P, goal: G, cost: F) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S) -> Option<K>,
+        G: FnMut(&P, &mut S) -> ExploreSignals,
+        H: Heuristic<P, S, K>,
+    {
+        let heuristic = &mut self.heuristic;
+        Self::weighted(
+            &mut self.context,
+            &mut self.neighbours,
+            start,
+            goal,
+            cost,
+            |p, context| heuristic.estimate(p, context),
+        )
+    }
+
+    fn weighted<K, F, G, E>(
+        context: &mut S,
+        neighbours: &mut N,
+        start: P,
+        mut goal: G,
+        mut cost: F,
+        mut heuristic: E,
+    ) -> Option<(K, Vec<P>)>
+    where
+        K: Copy + Ord + Add<Output = K> + Zero,
+        F: FnMut(&P, &P, &mut S) -> Option<K>,
+        G: FnMut(&P, &mut S) -> ExploreSignals,
+        E: FnMut(&P, &S) -> K,
+    {
+        astar_with(
+            start,
+            context,
+            &mut X::default(),
+            |p, context| neighbours.neighbours(p, context),
+            |p, context, dedup| {
+                if dedup.visit(p) {
+                    goal(p, context)
+                } else {
+                    ExploreSignals::Skip
+                }
+            },
+            |p, n, context, dedup| match dedup.admit(n) {
+                true => cost(p, n, context),
+                false => None,
+            },
+            |p, context, _| heuristic(p, context),
+        )
+    }
+}
+
+type PruneNeighbour<'a, P, S> = Box<dyn FnMut(&P, &P, &S) -> bool + 'a>;
+
+// NOTE: Frontier of a lazy exploration. Unlike `ExplorationIter` it does not hold on to the
+// context, so the context can be modified between calls to `step`.
+//
+// The neighbours of a yielded state are generated on the following `step`, so the exploration
+// sees whatever the context looks like at that time.
+pub struct ExplorationCursor<'a, P, S, B: Bag<P>> {
+    open: Frontier<P, B>,
+    pending: Option<(P, Visit<P>)>,
+    seen: Option<HashSet<P>>,
+    max_depth: Option<usize>,
+    filters: Vec<PruneNeighbour<'a, P, S>>,
+}
+
+impl<'a, P, S, B> ExplorationCursor<'a, P, S, B>
+where
+    P: IterateNeighbours<S> + Copy + Hash + Eq,
+    S: IterateNeighboursContext,
+    B: Bag<P>,
+{
+    pub fn new(start: P) -> Self {
+        Self {
+            open: Frontier::new(start),
+            pending: None,
+            seen: None,
+            max_depth: None,
+            filters: vec![],
+        }
+    }
+
+    // NOTE: Every state is yielded at most once.
+    pub fn avoid_identical(mut self) -> Self {
+        self.seen = Some(HashSet::new());
+        self
+    }
+
+    // NOTE: States at `depth` are still yielded, but not expanded.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    // NOTE: Only neighbours `n` of `p` for which `f(p, n, context)` holds are put in the bag.
+    pub fn filter_neighbours<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&P, &P, &S) -> bool + 'a,
+    {
+        self.filters.push(Box::new(f));
+        self
+    }
+
+    pub fn iter(self, context: &'a S) -> ExplorationIter<'a, P, S, B> {
+        ExplorationIter {
+            context,
+            cursor: self,
+        }
+    }
+
+    pub fn step(&mut self, context: &S) -> Option<(P, usize, Option<P>)> {
+        if let Some((p, visit)) = self.pending.take() {
+            self.expand(p, visit, context);
+        }
+
+        while let Some((p, visit)) = self.open.get() {
+            if let Some(seen) = &mut self.seen {
+                if !seen.insert(p) {
+                    continue;
+                }
+            }
+
+            self.pending = Some((p, visit));
+            return Some((p, visit.depth, visit.parent));
+        }
+
+        None
+    }
+
+    fn expand(&mut self, p: P, visit: Visit<P>, context: &S) {
+        if self
+            .max_depth
+            .is_some_and(|max_depth| visit.depth >= max_depth)
+        {
+            return;
+        }
+
+        for n in p.neighbours(context) {
+            if self.seen.as_ref().is_some_and(|seen| seen.contains(&n)) {
+                continue;
+            }
+            if self.filters.iter_mut().all(|f| f(&p, &n, context)) {
+                self.open.put(
+                    n,
+                    Visit {
+                        parent: Some(p),
+                        depth: visit.depth + 1,
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub struct ExplorationIter<'a, P, S, B: Bag<P>> {
+    context: &'a S,
+    cursor: ExplorationCursor<'a, P, S, B>,
+}
+
+impl<'a, P, S, B> ExplorationIter<'a, P, S, B>
+where
+    P: IterateNeighbours<S> + Copy + Hash + Eq,
+    S: IterateNeighboursContext,
+    B: Bag<P>,
+{
+    pub fn avoid_identical(self) -> Self {
+        Self {
+            cursor: self.cursor.avoid_identical(),
+            ..self
+        }
+    }
+
+    pub fn max_depth(self, depth: usize) -> Self {
+        Self {
+            cursor: self.cursor.max_depth(depth),
+            ..self
+        }
+    }
+
+    pub fn filter_neighbours<F>(self, f: F) -> Self
+    where
+        F: FnMut(&P, &P, &S) -> bool + 'a,
+    {
+        Self {
+            cursor: self.cursor.filter_neighbours(f),
+            ..self
+        }
+    }
+}
+
+impl<P, S, B> Iterator for ExplorationIter<'_, P, S, B>
+where
+    P: IterateNeighbours<S> + Copy + Hash + Eq,
+    S: IterateNeighboursContext,
+    B: Bag<P>,
+{
+    type Item = (P, usize, Option<P>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.step(self.context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arraynd::{Array2d, CharArray2d},
+        geometric_traits::ManhattanDistance,
+        sketch::{QueueBag, StackBag},
+        vector::V2i32,
+    };
+
+    const MAZE: &str = r#"
+S.#....
+.##.##.
+...#...
+.#...#E
+"#;
+
+    fn maze_goal(p: &V2i32, maze: &mut CharArray2d, _: &mut ()) -> ExploreSignals {
+        match maze[*p] {
+            'E' => ExploreSignals::ReachedGoal,
+            _ => ExploreSignals::Explore,
+        }
+    }
+
+    fn maze_filter(_: &V2i32, n: &V2i32, maze: &mut CharArray2d, _: &mut ()) -> bool {
+        maze[*n] != '#'
+    }
+
+    const RISK: &str = r#"
+1163751
+1381373
+2136511
+3694931
+7463417
+"#;
+
+    fn risk_map() -> Array2d<u32> {
+        let map: Array2d<char> = RISK.parse().unwrap();
+        map.map(|c| c.to_digit(10).unwrap())
+    }
+
+    #[test]
+    fn dijkstra_cheapest_path() {
+        let map = risk_map();
+        let end = V2i32::from_xy(6, 4);
+        let mut exploration = Exploration::new(map, ());
+        let (cost, path) = exploration
+            .explore_dijkstra(
+                V2i32::from_xy(0, 0),
+                |p, _, _| match *p == end {
+                    true => ExploreSignals::ReachedGoal,
+                    false => ExploreSignals::Explore,
+                },
+                |_, n, map, _| map.get(*n).copied(),
+            )
+            .unwrap();
+
+        assert_eq!(cost, 28);
+        assert_eq!(path.first(), Some(&V2i32::from_xy(0, 0)));
+        assert_eq!(path.last(), Some(&end));
+        assert_eq!(
+            cost,
+            path.iter().skip(1).map(|p| exploration.context[*p]).sum()
+        );
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let end = V2i32::from_xy(6, 4);
+        let goal = |p: &V2i32, _: &mut Array2d<u32>, _: &mut ()| match *p == end {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        };
+        let cost = |_: &V2i32, n: &V2i32, map: &mut Array2d<u32>, _: &mut ()| map.get(*n).copied();
+
+        let dijkstra = Exploration::new(risk_map(), ())
+            .explore_dijkstra(V2i32::from_xy(0, 0), goal, cost)
+            .unwrap();
+        let astar = Exploration::new(risk_map(), ())
+            .explore_astar(V2i32::from_xy(0, 0), goal, cost, |p, _, _| {
+                p.manhattan_distance(&end) as u32
+            })
+            .unwrap();
+
+        assert_eq!(dijkstra.0, astar.0);
+    }
+
+    #[test]
+    fn dijkstra_unreachable() {
+        let map: Array2d<char> = "111\n119\n191".parse().unwrap();
+        let mut exploration = Exploration::new(map.map(|c| c.to_digit(10).unwrap()), ());
+        let result = exploration.explore_dijkstra(
+            V2i32::from_xy(0, 0),
+            |p, _, _| match *p == V2i32::from_xy(2, 2) {
+                true => ExploreSignals::ReachedGoal,
+                false => ExploreSignals::Explore,
+            },
+            |_, n, map, _| match map.get(*n) {
+                Some(&risk) if risk < 9 => Some(risk),
+                _ => None,
+            },
+        );
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn unit_costs_count_steps() {
+        let start = V2i32::from_xy(0, 0);
+        let end = V2i32::from_xy(6, 4);
+        let mut exploration = Exploration::new(risk_map(), ());
+        let (cost, path) = exploration
+            .explore_dijkstra(
+                start,
+                |p, _, _| match *p == end {
+                    true => ExploreSignals::ReachedGoal,
+                    false => ExploreSignals::Explore,
+                },
+                |_, _, _, _| Some(1),
+            )
+            .unwrap();
+
+        assert_eq!(cost, start.manhattan_distance(&end));
+        assert_eq!(path.len() as i32, cost + 1);
+    }
+
+    #[test]
+    fn breadth_first_result_has_shortest_path() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let end = maze.find_item(&'E').unwrap();
+        let mut exploration = Exploration::new(maze, ());
+        let result = exploration.explore_avoid_identical_traced::<_, _, QueueBag<_>>(
+            start,
+            maze_goal,
+            maze_filter,
+        );
+
+        assert_eq!(result.goal, Some(end));
+        assert_eq!(result.depth(&end), Some(11));
+        let path = result.path().unwrap();
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert!(result.visits >= result.expansions);
+    }
+
+    #[test]
+    fn depth_first_result_path_is_connected() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let mut exploration = Exploration::new(maze, ());
+        let result = exploration.explore_avoid_identical_traced::<_, _, StackBag<_>>(
+            start,
+            maze_goal,
+            maze_filter,
+        );
+
+        let path = result.path().unwrap();
+        assert_eq!(path[0], start);
+        for (depth, w) in path.windows(2).enumerate() {
+            assert_eq!(w[0].manhattan_distance(&w[1]), 1);
+            assert_eq!(result.parent(&w[1]), Some(w[0]));
+            assert_eq!(result.depth(&w[1]), Some(depth + 1));
+        }
+    }
+
+    #[test]
+    fn result_without_goal_records_reachable_states() {
+        let mut maze: CharArray2d = MAZE.parse().unwrap();
+        maze.replace_all(&'E', &'.');
+        let start = maze.find_item(&'S').unwrap();
+        let open_cells = maze.find_all_items::<V2i32>(&'.').len() + 1;
+        let mut exploration = Exploration::new(maze, ());
+        let result = exploration.explore_avoid_identical_traced::<_, _, QueueBag<_>>(
+            start,
+            maze_goal,
+            maze_filter,
+        );
+
+        assert!(!result.reached_goal());
+        assert_eq!(result.path(), None);
+        assert_eq!(result.visited.len(), open_cells);
+        assert_eq!(result.expansions, open_cells);
+        assert_eq!(result.path_to(&start), Some(vec![start]));
+    }
+
+    #[test]
+    fn iter_breadth_first_order() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let end = maze.find_item(&'E').unwrap();
+        let exploration = Exploration::<V2i32, _, _>::new(maze, ());
+        let visits: Vec<_> = exploration
+            .iter::<QueueBag<_>>(start)
+            .avoid_identical()
+            .filter_neighbours(|_, n, maze| maze[*n] != '#')
+            .collect();
+
+        assert_eq!(visits[0], (start, 0, None));
+        assert!(visits.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(
+            visits.iter().find(|(p, _, _)| *p == end).map(|v| v.1),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn iter_max_depth() {
+        let exploration = Exploration::<V2i32, _, _>::new(Array2d::new([5, 5], 0), ());
+        let count = exploration
+            .iter::<QueueBag<_>>(V2i32::from_xy(2, 2))
+            .avoid_identical()
+            .max_depth(1)
+            .count();
+
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn iter_max_depth_without_dedup() {
+        // NOTE: States are revisited, every entry keeps the depth it was put in the bag with.
+        let exploration = Exploration::<V2i32, _, _>::new(Array2d::new([3, 3], 0), ());
+        let visits: Vec<_> = exploration
+            .iter::<StackBag<_>>(V2i32::from_xy(1, 1))
+            .max_depth(2)
+            .collect();
+
+        assert_eq!(visits.len(), 1 + 4 + 4 * 3);
+        assert_eq!(
+            visits.iter().filter(|(_, depth, _)| *depth == 2).count(),
+            12
+        );
+    }
+
+    // NOTE: A bag defined outside of this crate, explorations only need `Bag`.
+    struct OldestLastBag(Vec<V2i32>);
+
+    impl Bag<V2i32> for OldestLastBag {
+        fn new() -> Self {
+            Self(vec![])
+        }
+        fn put(&mut self, t: V2i32) {
+            self.0.insert(0, t);
+        }
+        fn get(&mut self) -> Option<V2i32> {
+            self.0.pop()
+        }
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn custom_bag() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let end = maze.find_item(&'E').unwrap();
+        let mut exploration = Exploration::new(maze, ());
+        exploration.explore_avoid_identical::<_, _, OldestLastBag>(start, maze_goal, maze_filter);
+        let result = exploration.explore_avoid_identical_traced::<_, _, OldestLastBag>(
+            start,
+            maze_goal,
+            maze_filter,
+        );
+
+        assert_eq!(result.depth(&end), Some(11));
+        assert_eq!(result.path().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn cursor_allows_modifying_context() {
+        let mut maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let mut cursor = ExplorationCursor::<V2i32, CharArray2d, StackBag<_>>::new(start)
+            .avoid_identical()
+            .filter_neighbours(|_, n, maze| maze[*n] == '.');
+
+        let mut steps = 0;
+        while let Some((p, _, _)) = cursor.step(&maze) {
+            maze.set(p, 'o');
+            steps += 1;
+        }
+
+        assert_eq!(steps, maze.find_all_items::<V2i32>(&'o').len());
+        assert_eq!(maze.find_item(&'.'), None);
+    }
+
+    #[test]
+    fn builder_from_arraynd() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let end = maze.find_item(&'E').unwrap();
+        let mut exploration = ExplorationBuilder::from_arraynd(maze)
+            .filter_neighbours(|_, n, maze| maze[*n] != '#')
+            .with_heuristic(|p: &V2i32, _: &CharArray2d| p.manhattan_distance(&end))
+            .avoid_identical()
+            .build();
+
+        let goal = |p: &V2i32, _: &mut CharArray2d| match *p == end {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        };
+        let (cost, path) = exploration.astar(start, goal, |_, _, _| Some(1)).unwrap();
+        assert_eq!(cost, 11);
+        assert_eq!(path.len(), 12);
+
+        let result = exploration.explore::<_, QueueBag<_>>(start, goal);
+        assert_eq!(result.depth(&end), Some(11));
+    }
+
+    #[test]
+    fn builder_from_torus() {
+        let map: CharArray2d = "S.#..\n..#..\n###.#\n..#.E\n".parse().unwrap();
+        let start = map.find_item(&'S').unwrap();
+        let end = map.find_item(&'E').unwrap();
+
+        let mut bounded = ExplorationBuilder::from_arraynd(map.clone())
+            .filter_neighbours(|_, n, map| map[*n] != '#')
+            .avoid_identical()
+            .build();
+        let goal = |p: &V2i32, _: &mut _| match *p == end {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        };
+        assert!(!bounded
+            .explore::<_, QueueBag<_>>(start, goal)
+            .reached_goal());
+
+        let mut torus = ExplorationBuilder::from_torus(map)
+            .filter_neighbours(|_, n, map| map[*n] != '#')
+            .avoid_identical()
+            .build();
+        let goal = |p: &V2i32, _: &mut _| match *p == end {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        };
+        let result = torus.explore::<_, QueueBag<_>>(start, goal);
+        assert_eq!(
+            result.path(),
+            Some(vec![
+                V2i32::from_xy(0, 0),
+                V2i32::from_xy(4, 0),
+                V2i32::from_xy(4, 3)
+            ])
+        );
+    }
+
+    #[test]
+    fn builder_custom_neighbours() {
+        // NOTE: Fewest operations `x + 1` or `x * 2` to get from 1 to 100.
+        let mut exploration = ExplorationBuilder::new()
+            .with_neighbours(|x: &u32, _: &()| vec![x + 1, x * 2])
+            .filter_neighbours(|_, n, _| *n <= 100)
+            .avoid_identical()
+            .build();
+        let result = exploration.explore::<_, QueueBag<_>>(1, |x, _| match *x == 100 {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        });
+
+        assert_eq!(result.path(), Some(vec![1, 2, 3, 6, 12, 24, 25, 50, 100]));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Walker {
+        position: V2i32,
+        energy: i32,
+    }
+
+    impl PointKeyValue for Walker {
+        type K = V2i32;
+        type V = i32;
+
+        fn get_key(&self) -> Self::K {
+            self.position
+        }
+        fn get_value(&self) -> Self::V {
+            self.energy
+        }
+        fn compare_values(_k: &Self::K, a: &Self::V, b: &Self::V) -> Option<Ordering> {
+            Some(a.cmp(b))
+        }
+    }
+
+    #[test]
+    fn builder_dedup_policies() {
+        let grid = Array2d::new([4, 4], 0);
+        let neighbours = |w: &Walker, grid: &Array2d<i32>| {
+            w.position
+                .neighbours(grid)
+                .into_iter()
+                .map(|position| Walker {
+                    position,
+                    energy: w.energy - 1,
+                })
+                .filter(|w| w.energy >= 0)
+                .collect()
+        };
+        let start = Walker {
+            position: V2i32::from_xy(0, 0),
+            energy: 6,
+        };
+        let goal = |_: &Walker, _: &mut Array2d<i32>| ExploreSignals::Explore;
+
+        let all = ExplorationBuilder::new()
+            .with_context(grid.clone())
+            .with_neighbours(neighbours)
+            .build()
+            .explore::<_, QueueBag<_>>(start, goal);
+        let identical = ExplorationBuilder::new()
+            .with_context(grid.clone())
+            .with_neighbours(neighbours)
+            .avoid_identical()
+            .build()
+            .explore::<_, QueueBag<_>>(start, goal);
+        let worse = ExplorationBuilder::new()
+            .with_context(grid)
+            .with_neighbours(neighbours)
+            .avoid_worse()
+            .build()
+            .explore::<_, QueueBag<_>>(start, goal);
+
+        assert!(all.expansions > identical.expansions);
+        assert!(all.expansions > worse.expansions);
+        assert!(worse
+            .visited
+            .keys()
+            .all(|w| w.energy == 6 - w.position.manhattan_distance(&start.position)));
+    }
+
+    // NOTE: Sorting puzzle, either swap the first two items or rotate everything to the left.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Shuffle([u8; 5]);
+
+    impl IterateNeighbours<()> for Shuffle {
+        fn neighbours(&self, _context: &()) -> Vec<Self> {
+            let mut swapped = self.0;
+            swapped.swap(0, 1);
+            let mut rotated = self.0;
+            rotated.rotate_left(1);
+            vec![Shuffle(swapped), Shuffle(rotated)]
+        }
+    }
+
+    fn shuffle_reverse(p: &Shuffle, _context: &()) -> Vec<Shuffle> {
+        let mut swapped = p.0;
+        swapped.swap(0, 1);
+        let mut rotated = p.0;
+        rotated.rotate_right(1);
+        vec![Shuffle(swapped), Shuffle(rotated)]
+    }
+
+    const SHUFFLE_START: Shuffle = Shuffle([0, 1, 2, 3, 4]);
+    const SHUFFLE_END: Shuffle = Shuffle([4, 3, 2, 1, 0]);
+
+    fn shuffle_goal(p: &Shuffle, _: &mut (), _: &mut ()) -> ExploreSignals {
+        match *p == SHUFFLE_END {
+            true => ExploreSignals::ReachedGoal,
+            false => ExploreSignals::Explore,
+        }
+    }
+
+    fn shuffle_depth() -> usize {
+        Exploration::new((), ())
+            .explore_avoid_identical_traced::<_, _, QueueBag<_>>(
+                SHUFFLE_START,
+                shuffle_goal,
+                |_, _, _, _| true,
+            )
+            .depth(&SHUFFLE_END)
+            .unwrap()
+    }
+
+    fn assert_valid_shuffle_path(path: &[Shuffle]) {
+        assert_eq!(path.first(), Some(&SHUFFLE_START));
+        assert_eq!(path.last(), Some(&SHUFFLE_END));
+        assert!(path
+            .windows(2)
+            .all(|w| w[0].neighbours(&()).contains(&w[1])));
+    }
+
+    #[test]
+    fn bidirectional_finds_shortest_path() {
+        let path = Exploration::new((), ())
+            .explore_bidirectional(
+                SHUFFLE_START,
+                SHUFFLE_END,
+                |_, _, _| ExploreSignals::Explore,
+                |_, _, _, _| true,
+                shuffle_reverse,
+            )
+            .unwrap();
+
+        assert_valid_shuffle_path(&path);
+        assert_eq!(path.len() - 1, shuffle_depth());
+    }
+
+    #[test]
+    fn iddfs_finds_shortest_path() {
+        let depth = shuffle_depth();
+        let mut exploration = Exploration::new((), ());
+        let path = exploration
+            .explore_iddfs(SHUFFLE_START, depth, shuffle_goal, |_, _, _, _| true)
+            .unwrap();
+
+        assert_valid_shuffle_path(&path);
+        assert_eq!(path.len() - 1, depth);
+        assert_eq!(
+            exploration.explore_iddfs(SHUFFLE_START, depth - 1, shuffle_goal, |_, _, _, _| true),
+            None
+        );
+    }
+
+    #[test]
+    fn idastar_matches_breadth_first() {
+        let (cost, path) = Exploration::new((), ())
+            .explore_idastar(
+                SHUFFLE_START,
+                shuffle_goal,
+                |_, _, _, _| Some(1),
+                |_, _, _| 0,
+            )
+            .unwrap();
+
+        assert_valid_shuffle_path(&path);
+        assert_eq!(cost, shuffle_depth());
+    }
+
+    #[test]
+    fn idastar_on_grid() {
+        let maze: CharArray2d = MAZE.parse().unwrap();
+        let start = maze.find_item(&'S').unwrap();
+        let end = maze.find_item(&'E').unwrap();
+        let (cost, path) = Exploration::new(maze, ())
+            .explore_idastar(
+                start,
+                maze_goal,
+                |_, n, maze, _| match maze[*n] {
+                    '#' => None,
+                    _ => Some(1),
+                },
+                |p, _, _| p.manhattan_distance(&end),
+            )
+            .unwrap();
+
+        assert_eq!(cost, 11);
+        assert_eq!(path.len(), 12);
     }
 }
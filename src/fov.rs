@@ -0,0 +1,242 @@
+use super::{
+    arraynd::Array2d,
+    line_iterator::{LineIterator, LineMode},
+    vector::V2i32,
+};
+
+// NOTE: Slope `num / den` of a cell edge as seen from the origin, `den` is always positive.
+#[derive(Clone, Copy, Debug)]
+struct Slope {
+    num: i64,
+    den: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Row {
+    depth: i64,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    // NOTE: Columns whose centre lies between the slopes, ties included on both sides.
+    fn columns(&self) -> std::ops::RangeInclusive<i64> {
+        let min = (2 * self.depth * self.start.num + self.start.den).div_euclid(2 * self.start.den);
+        let max = -(-(2 * self.depth * self.end.num - self.end.den)).div_euclid(2 * self.end.den);
+        min..=max
+    }
+
+    // NOTE: Cells are only revealed when their centre is inside the row's sector, which makes
+    // visibility symmetric.
+    fn is_symmetric(&self, column: i64) -> bool {
+        column * self.start.den >= self.depth * self.start.num
+            && column * self.end.den <= self.depth * self.end.num
+    }
+
+    fn next(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+fn cell_slope(depth: i64, column: i64) -> Slope {
+    Slope {
+        num: 2 * column - 1,
+        den: 2 * depth,
+    }
+}
+
+impl<T> Array2d<T> {
+    // NOTE: Symmetric shadowcasting: `b` is visible from `a` exactly when `a` is visible from
+    // `b`. Opaque cells are visible themselves but hide what is behind them, positions outside
+    // of the array count as opaque. Cells further than `radius` from `origin` are not visible.
+    pub fn field_of_view<F>(&self, origin: V2i32, radius: i32, opaque: F) -> Array2d<bool>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut visible = Array2d::with_dimensions(self.width(), self.height(), false);
+        if self.get(origin).is_none() || radius < 0 {
+            return visible;
+        }
+        visible.set(origin, true);
+
+        let radius = radius as i64;
+        let is_opaque = |p: V2i32| !self.get(p).is_some_and(|v| !opaque(v));
+        // NOTE: Direction of increasing depth and of increasing column for each quadrant.
+        let quadrants = [
+            (V2i32::from_xy(0, -1), V2i32::from_xy(1, 0)),
+            (V2i32::from_xy(1, 0), V2i32::from_xy(0, 1)),
+            (V2i32::from_xy(0, 1), V2i32::from_xy(1, 0)),
+            (V2i32::from_xy(-1, 0), V2i32::from_xy(0, 1)),
+        ];
+
+        let mut rows = vec![];
+        for (forward, sideways) in quadrants {
+            let cell = |depth: i64, column: i64| {
+                origin + forward * depth as i32 + sideways * column as i32
+            };
+            rows.push(Row {
+                depth: 1,
+                start: Slope { num: -1, den: 1 },
+                end: Slope { num: 1, den: 1 },
+            });
+            while let Some(mut row) = rows.pop() {
+                if row.depth > radius {
+                    continue;
+                }
+                let mut previous: Option<bool> = None;
+                for column in row.columns() {
+                    let p = cell(row.depth, column);
+                    let wall = is_opaque(p);
+                    let in_radius = row.depth * row.depth + column * column <= radius * radius;
+                    if in_radius && (wall || row.is_symmetric(column)) {
+                        visible.set(p, true);
+                    }
+                    if previous == Some(true) && !wall {
+                        row.start = cell_slope(row.depth, column);
+                    }
+                    if previous == Some(false) && wall {
+                        rows.push(Row {
+                            end: cell_slope(row.depth, column),
+                            ..row.next()
+                        });
+                    }
+                    previous = Some(wall);
+                }
+                if previous == Some(false) {
+                    rows.push(row.next());
+                }
+            }
+        }
+        visible
+    }
+
+    pub fn has_line_of_sight<F>(&self, a: V2i32, b: V2i32, opaque: F) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.has_line_of_sight_with_mode(a, b, LineMode::Bresenham, opaque)
+    }
+
+    // NOTE: True when every cell strictly between `a` and `b` is inside the array and not opaque.
+    // The endpoints themselves may be opaque, a wall can be seen. `Supercover` is the strictest
+    // mode, it is blocked by walls the segment only grazes.
+    pub fn has_line_of_sight_with_mode<F>(
+        &self,
+        a: V2i32,
+        b: V2i32,
+        mode: LineMode,
+        opaque: F,
+    ) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        if self.get(a).is_none() || self.get(b).is_none() {
+            return false;
+        }
+        LineIterator::<false, 2>::with_mode(a, b, mode)
+            .skip(1)
+            .all(|p| self.get(p).is_some_and(|v| !opaque(v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arraynd::{Array2d, CharArray2d},
+        line_iterator::LineMode,
+        vector::V2i32,
+    };
+
+    fn render(map: &CharArray2d, visible: &Array2d<bool>) -> String {
+        let mut result = map.clone();
+        for (cell, seen) in result.data.iter_mut().zip(visible.data.iter()) {
+            if !seen {
+                *cell = ' ';
+            }
+        }
+        result.to_string()
+    }
+
+    #[test]
+    fn shadowcasting() {
+        let map: CharArray2d = "\
+#########
+#.......#
+#.......#
+#...#...#
+#.......#
+#.......#
+#########
+"
+        .parse()
+        .unwrap();
+        let origin = map.find_item(&'.').unwrap() + V2i32::from_xy(3, 4);
+        let visible = map.field_of_view(origin, 20, |c| *c == '#');
+        assert_eq!(
+            render(&map, &visible),
+            "\
+#### ####
+#... ...#
+#... ...#
+#...#...#
+#.......#
+#.......#
+#########
+"
+        );
+
+        let near = map.field_of_view(origin, 1, |c| *c == '#');
+        assert_eq!(near.data.iter().filter(|v| **v).count(), 5);
+        assert_eq!(
+            map.field_of_view(V2i32::from_xy(-1, 0), 5, |c| *c == '#'),
+            Array2d::with_dimensions(9, 7, false)
+        );
+    }
+
+    #[test]
+    fn symmetry() {
+        let map: CharArray2d = "\
+..#.....
+....#...
+.#....#.
+......#.
+..##....
+"
+        .parse()
+        .unwrap();
+        let floor = map.find_all_items(&'.');
+        for a in &floor {
+            let from_a = map.field_of_view(*a, 100, |c| *c == '#');
+            for b in &floor {
+                let from_b = map.field_of_view(*b, 100, |c| *c == '#');
+                assert_eq!(from_a.get(*b), from_b.get(*a), "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let map: CharArray2d = "\
+.....
+..#..
+.....
+"
+        .parse()
+        .unwrap();
+        let wall = |c: &char| *c == '#';
+        assert!(map.has_line_of_sight(V2i32::from_xy(0, 0), V2i32::from_xy(4, 0), wall));
+        assert!(!map.has_line_of_sight(V2i32::from_xy(0, 1), V2i32::from_xy(4, 1), wall));
+        assert!(map.has_line_of_sight(V2i32::from_xy(0, 1), V2i32::from_xy(2, 1), wall));
+        assert!(map.has_line_of_sight(V2i32::from_xy(1, 0), V2i32::from_xy(4, 1), wall));
+        assert!(!map.has_line_of_sight_with_mode(
+            V2i32::from_xy(1, 0),
+            V2i32::from_xy(4, 1),
+            LineMode::Supercover,
+            wall
+        ));
+        assert!(!map.has_line_of_sight(V2i32::from_xy(0, 0), V2i32::from_xy(5, 0), wall));
+    }
+}
@@ -0,0 +1,580 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::arraynd::Array2d;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    // NOTE: Magic number or variant this module does not read, e.g. 16-bit PGM.
+    Unsupported(String),
+    Malformed(&'static str),
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(io) => write!(f, "IO error: {io}"),
+            ImageError::Unsupported(what) => write!(f, "Unsupported image: {what}"),
+            ImageError::Malformed(what) => write!(f, "Malformed image: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(value: std::io::Error) -> Self {
+        ImageError::Io(value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PnmEncoding {
+    // NOTE: P2/P3, human readable.
+    Ascii,
+    // NOTE: P5/P6.
+    Binary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    Ppm(PnmEncoding),
+    Bmp,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm(_) => "ppm",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+}
+
+// NOTE: Row `y = 0` of the array is the top row of the image.
+impl<T> Array2d<T> {
+    pub fn write_ppm<W, F>(
+        &self,
+        mut writer: W,
+        encoding: PnmEncoding,
+        colour: F,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+        F: Fn(&T) -> [u8; 3],
+    {
+        match encoding {
+            PnmEncoding::Ascii => {
+                writeln!(writer, "P3\n{} {}\n255", self.dims[0], self.dims[1])?;
+                for row in self.data.chunks(self.dims[0].max(1)) {
+                    let line: Vec<String> = row
+                        .iter()
+                        .map(|x| {
+                            let [r, g, b] = colour(x);
+                            format!("{r} {g} {b}")
+                        })
+                        .collect();
+                    writeln!(writer, "{}", line.join("  "))?;
+                }
+            }
+            PnmEncoding::Binary => {
+                write!(writer, "P6\n{} {}\n255\n", self.dims[0], self.dims[1])?;
+                let bytes: Vec<u8> = self.data.iter().flat_map(&colour).collect();
+                writer.write_all(&bytes)?;
+            }
+        }
+        writer.flush()
+    }
+
+    pub fn write_pgm<W, F>(
+        &self,
+        mut writer: W,
+        encoding: PnmEncoding,
+        grey: F,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+        F: Fn(&T) -> u8,
+    {
+        match encoding {
+            PnmEncoding::Ascii => {
+                writeln!(writer, "P2\n{} {}\n255", self.dims[0], self.dims[1])?;
+                for row in self.data.chunks(self.dims[0].max(1)) {
+                    let line: Vec<String> = row.iter().map(|x| grey(x).to_string()).collect();
+                    writeln!(writer, "{}", line.join(" "))?;
+                }
+            }
+            PnmEncoding::Binary => {
+                write!(writer, "P5\n{} {}\n255\n", self.dims[0], self.dims[1])?;
+                let bytes: Vec<u8> = self.data.iter().map(&grey).collect();
+                writer.write_all(&bytes)?;
+            }
+        }
+        writer.flush()
+    }
+
+    // NOTE: Uncompressed 24-bit BMP.
+    pub fn write_bmp<W, F>(&self, mut writer: W, colour: F) -> std::io::Result<()>
+    where
+        W: Write,
+        F: Fn(&T) -> [u8; 3],
+    {
+        let (width, height) = (self.dims[0], self.dims[1]);
+        let row_size = (3 * width).next_multiple_of(4);
+        let image_size = row_size * height;
+        const HEADER_SIZE: usize = 14 + 40;
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + image_size);
+        bytes.extend(b"BM");
+        bytes.extend(((HEADER_SIZE + image_size) as u32).to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend((HEADER_SIZE as u32).to_le_bytes());
+
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend((width as i32).to_le_bytes());
+        bytes.extend((height as i32).to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(24u16.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend((image_size as u32).to_le_bytes());
+        // NOTE: 72 DPI.
+        bytes.extend(2835u32.to_le_bytes());
+        bytes.extend(2835u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+
+        // NOTE: Rows are stored bottom-up, pixels as BGR.
+        for y in (0..height).rev() {
+            let start = bytes.len();
+            for x in &self.data[y * width..(y + 1) * width] {
+                let [r, g, b] = colour(x);
+                bytes.extend([b, g, r]);
+            }
+            bytes.resize(start + row_size, 0);
+        }
+
+        writer.write_all(&bytes)?;
+        writer.flush()
+    }
+
+    // NOTE: Picks the writer by `format`.
+    pub fn write_image<W, F>(
+        &self,
+        writer: W,
+        format: ImageFormat,
+        colour: F,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+        F: Fn(&T) -> [u8; 3],
+    {
+        match format {
+            ImageFormat::Ppm(encoding) => self.write_ppm(writer, encoding, colour),
+            ImageFormat::Bmp => self.write_bmp(writer, colour),
+        }
+    }
+
+    pub fn save_image<P, F>(&self, path: P, format: ImageFormat, colour: F) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+        F: Fn(&T) -> [u8; 3],
+    {
+        let file = BufWriter::new(File::create(path)?);
+        self.write_image(file, format, colour)
+    }
+}
+
+struct PnmReader {
+    bytes: Vec<u8>,
+    at: usize,
+}
+
+impl PnmReader {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&b) = self.bytes.get(self.at) {
+            if b == b'#' {
+                while self.bytes.get(self.at).is_some_and(|b| *b != b'\n') {
+                    self.at += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.at += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&[u8], ImageError> {
+        self.skip_whitespace_and_comments();
+        let start = self.at;
+        while self
+            .bytes
+            .get(self.at)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.at += 1;
+        }
+        match start == self.at {
+            true => Err(ImageError::Malformed("unexpected end of file")),
+            false => Ok(&self.bytes[start..self.at]),
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        std::str::from_utf8(self.token()?)
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .ok_or(ImageError::Malformed("expected a number"))
+    }
+
+    // NOTE: Magic number, width, height and maximum value. Leaves the reader on the first byte of
+    // binary data.
+    fn header(&mut self) -> Result<(Vec<u8>, usize, usize, usize), ImageError> {
+        let magic = self.token()?.to_vec();
+        let width = self.number()?;
+        let height = self.number()?;
+        let max = self.number()?;
+        if max == 0 || max > 255 {
+            return Err(ImageError::Unsupported(format!("maximum value {max}")));
+        }
+        self.at += 1;
+        Ok((magic, width, height, max))
+    }
+
+    fn samples(&mut self, count: usize, binary: bool, max: usize) -> Result<Vec<u8>, ImageError> {
+        let too_large = ImageError::Malformed("sample larger than the maximum value");
+        let samples = if binary {
+            let samples = self
+                .at
+                .checked_add(count)
+                .and_then(|end| self.bytes.get(self.at..end))
+                .ok_or(ImageError::Malformed("unexpected end of file"))?
+                .to_vec();
+            if samples.iter().any(|x| *x as usize > max) {
+                return Err(too_large);
+            }
+            self.at += count;
+            samples
+        } else {
+            // NOTE: Every sample takes at least one byte, so a header with huge dimensions cannot
+            // make this allocate more than the file itself.
+            let remaining = self.bytes.len().saturating_sub(self.at);
+            let mut samples = Vec::with_capacity(count.min(remaining));
+            for _ in 0..count {
+                let x = self.number()?;
+                if x > max {
+                    return Err(too_large);
+                }
+                samples.push(x as u8);
+            }
+            samples
+        };
+
+        Ok(samples
+            .into_iter()
+            .map(|x| (x as usize * 255 / max) as u8)
+            .collect())
+    }
+
+    // NOTE: Number of samples in a `width` by `height` image with `channels` samples per pixel.
+    fn sample_count(width: usize, height: usize, channels: usize) -> Result<usize, ImageError> {
+        width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(channels))
+            .ok_or(ImageError::Malformed("image dimensions too large"))
+    }
+}
+
+fn read_all<R: Read>(mut reader: R) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn from_pixels<T>(width: usize, height: usize, data: Vec<T>) -> Array2d<T> {
+    Array2d {
+        data,
+        dims: [width, height],
+        dim_strides: [1, width],
+    }
+}
+
+impl Array2d<[u8; 3]> {
+    // NOTE: Reads P3 and P6, samples are rescaled to `0..=255`.
+    pub fn read_ppm<R: Read>(reader: R) -> Result<Self, ImageError> {
+        let mut pnm = PnmReader {
+            bytes: read_all(reader)?,
+            at: 0,
+        };
+        let (magic, width, height, max) = pnm.header()?;
+        let binary = match magic.as_slice() {
+            b"P3" => false,
+            b"P6" => true,
+            _ => {
+                return Err(ImageError::Unsupported(
+                    String::from_utf8_lossy(&magic).into_owned(),
+                ))
+            }
+        };
+        let count = PnmReader::sample_count(width, height, 3)?;
+        let samples = pnm.samples(count, binary, max)?;
+        let data = samples.chunks(3).map(|c| [c[0], c[1], c[2]]).collect();
+        Ok(from_pixels(width, height, data))
+    }
+
+    // NOTE: Reads uncompressed 24 and 32-bit BMP, either bottom-up or top-down.
+    pub fn read_bmp<R: Read>(reader: R) -> Result<Self, ImageError> {
+        let bytes = read_all(reader)?;
+        let u32_at = |i: usize| {
+            bytes
+                .get(i..i + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or(ImageError::Malformed("header too short"))
+        };
+        if !bytes.starts_with(b"BM") {
+            return Err(ImageError::Unsupported("missing BM signature".into()));
+        }
+
+        let offset = u32_at(10)? as usize;
+        if u32_at(14)? < 40 {
+            return Err(ImageError::Unsupported("BMP core header".into()));
+        }
+        let width = u32_at(18)? as i32;
+        let height = u32_at(22)? as i32;
+        let bits = u32_at(28)? & 0xffff;
+        let compression = u32_at(30)?;
+        if width < 0 || compression != 0 || (bits != 24 && bits != 32) {
+            return Err(ImageError::Unsupported(format!(
+                "{bits}-bit BMP with compression {compression}"
+            )));
+        }
+
+        let (width, top_down, height) =
+            (width as usize, height < 0, height.unsigned_abs() as usize);
+        let pixel_size = bits as usize / 8;
+        let row_size = (pixel_size * width).next_multiple_of(4);
+        let end = row_size
+            .checked_mul(height)
+            .and_then(|n| n.checked_add(offset));
+        if !end.is_some_and(|end| end <= bytes.len()) {
+            return Err(ImageError::Malformed("unexpected end of file"));
+        }
+
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = if top_down { y } else { height - 1 - y };
+            let start = offset + row * row_size;
+            for pixel in bytes[start..start + pixel_size * width].chunks(pixel_size) {
+                data.push([pixel[2], pixel[1], pixel[0]]);
+            }
+        }
+        Ok(from_pixels(width, height, data))
+    }
+}
+
+impl Array2d<u8> {
+    // NOTE: Reads P2 and P5, samples are rescaled to `0..=255`.
+    pub fn read_pgm<R: Read>(reader: R) -> Result<Self, ImageError> {
+        let mut pnm = PnmReader {
+            bytes: read_all(reader)?,
+            at: 0,
+        };
+        let (magic, width, height, max) = pnm.header()?;
+        let binary = match magic.as_slice() {
+            b"P2" => false,
+            b"P5" => true,
+            _ => {
+                return Err(ImageError::Unsupported(
+                    String::from_utf8_lossy(&magic).into_owned(),
+                ))
+            }
+        };
+        let count = PnmReader::sample_count(width, height, 1)?;
+        let data = pnm.samples(count, binary, max)?;
+        Ok(from_pixels(width, height, data))
+    }
+}
+
+// NOTE: Writes an animation as numbered files `{prefix}00000.{ext}`, `{prefix}00001.{ext}`, ...
+// Example:
+//   let mut frames = FrameWriter::new("out", "life_", ImageFormat::Bmp);
+//   frames.write(&grid, |c| if *c == '#' { [255; 3] } else { [0; 3] })?;
+#[derive(Debug, Clone)]
+pub struct FrameWriter {
+    directory: PathBuf,
+    prefix: String,
+    format: ImageFormat,
+    pub next_frame: usize,
+}
+
+impl FrameWriter {
+    pub fn new<P: Into<PathBuf>>(directory: P, prefix: &str, format: ImageFormat) -> Self {
+        Self {
+            directory: directory.into(),
+            prefix: prefix.to_owned(),
+            format,
+            next_frame: 0,
+        }
+    }
+
+    pub fn frame_path(&self, frame: usize) -> PathBuf {
+        self.directory.join(format!(
+            "{}{:05}.{}",
+            self.prefix,
+            frame,
+            self.format.extension()
+        ))
+    }
+
+    // NOTE: Returns the path of the written frame.
+    pub fn write<T, F>(&mut self, array: &Array2d<T>, colour: F) -> Result<PathBuf, ImageError>
+    where
+        F: Fn(&T) -> [u8; 3],
+    {
+        let path = self.frame_path(self.next_frame);
+        array.save_image(&path, self.format, colour)?;
+        self.next_frame += 1;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameWriter, ImageError, ImageFormat, PnmEncoding};
+    use crate::arraynd::{Array2d, CharArray2d};
+
+    fn colour(c: &char) -> [u8; 3] {
+        match c {
+            '#' => [255, 255, 255],
+            'r' => [200, 10, 20],
+            _ => [0, 0, 0],
+        }
+    }
+
+    fn sample() -> CharArray2d {
+        "#.r\n.#.\n".parse().unwrap()
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let image = sample();
+        let expected = image.map(colour);
+
+        let mut ascii = vec![];
+        image
+            .write_ppm(&mut ascii, PnmEncoding::Ascii, colour)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(ascii.clone()).unwrap(),
+            "P3\n3 2\n255\n255 255 255  0 0 0  200 10 20\n0 0 0  255 255 255  0 0 0\n"
+        );
+        assert_eq!(Array2d::read_ppm(ascii.as_slice()).unwrap(), expected);
+
+        let mut binary = vec![];
+        image
+            .write_ppm(&mut binary, PnmEncoding::Binary, colour)
+            .unwrap();
+        assert_eq!(binary.len(), "P6\n3 2\n255\n".len() + 18);
+        assert_eq!(Array2d::read_ppm(binary.as_slice()).unwrap(), expected);
+    }
+
+    #[test]
+    fn pgm_round_trip() {
+        let image = Array2d::from_slice([2, 2], &[0u8, 64, 128, 255]);
+        for encoding in [PnmEncoding::Ascii, PnmEncoding::Binary] {
+            let mut bytes = vec![];
+            image.write_pgm(&mut bytes, encoding, |x| *x).unwrap();
+            assert_eq!(Array2d::read_pgm(bytes.as_slice()).unwrap(), image);
+        }
+
+        let commented = "P2\n# made by hand\n2 1 # size\n15\n0 15\n";
+        assert_eq!(
+            Array2d::read_pgm(commented.as_bytes()).unwrap().data,
+            vec![0, 255]
+        );
+        assert!(matches!(
+            Array2d::read_pgm("P2\n2 1\n65535\n0 0\n".as_bytes()),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert!(matches!(
+            Array2d::read_pgm("P3\n1 1\n255\n0 0 0\n".as_bytes()),
+            Err(ImageError::Unsupported(_))
+        ));
+        assert!(matches!(
+            Array2d::read_pgm("P5\n2 2\n255\n\x01".as_bytes()),
+            Err(ImageError::Malformed(_))
+        ));
+        assert!(matches!(
+            Array2d::read_pgm(&b"P5\n1 2\n100\n\x10\xc8"[..]),
+            Err(ImageError::Malformed(_))
+        ));
+        for huge in [
+            "P5 4294967296 4294967296 255\n",
+            "P2 4294967295 4294967295 255\n0",
+        ] {
+            assert!(matches!(
+                Array2d::read_pgm(huge.as_bytes()),
+                Err(ImageError::Malformed(_))
+            ));
+        }
+        assert!(matches!(
+            Array2d::read_ppm("P6 6148914691236517206 1 255\n".as_bytes()),
+            Err(ImageError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let image = sample();
+        let mut bytes = vec![];
+        image.write_bmp(&mut bytes, colour).unwrap();
+        // NOTE: 3 pixels take 9 bytes, padded to 12.
+        assert_eq!(bytes.len(), 54 + 2 * 12);
+        assert_eq!(&bytes[54..57], &[0, 0, 0]);
+        assert_eq!(&bytes[66..69], &[255, 255, 255]);
+        assert_eq!(
+            Array2d::read_bmp(bytes.as_slice()).unwrap(),
+            image.map(colour)
+        );
+
+        // NOTE: Negative height marks a top-down bitmap.
+        let mut top_down = bytes.clone();
+        top_down[22..26].copy_from_slice(&(-2i32).to_le_bytes());
+        let flipped = Array2d::read_bmp(top_down.as_slice()).unwrap();
+        assert_eq!(flipped.data[..3], image.map(colour).data[3..]);
+
+        assert!(Array2d::read_bmp(&bytes[..60]).is_err());
+        let mut huge = bytes.clone();
+        huge[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+        huge[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+        huge[28..30].copy_from_slice(&32u16.to_le_bytes());
+        assert!(matches!(
+            Array2d::read_bmp(huge.as_slice()),
+            Err(ImageError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn frames() {
+        let directory = std::env::temp_dir().join(format!("lk_math_frames_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut frames =
+            FrameWriter::new(&directory, "frame_", ImageFormat::Ppm(PnmEncoding::Binary));
+        let image = sample();
+        let first = frames.write(&image, colour).unwrap();
+        let second = frames.write(&image, colour).unwrap();
+        assert_eq!(first, directory.join("frame_00000.ppm"));
+        assert_eq!(second, directory.join("frame_00001.ppm"));
+
+        let file = std::fs::File::open(&second).unwrap();
+        assert_eq!(Array2d::read_ppm(file).unwrap(), image.map(colour));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,23 +1,32 @@
 pub mod prelude;
 
 pub mod aabb;
+pub mod array_view;
 pub mod arraynd;
 pub mod bijection;
+pub mod chunked_grid;
 pub mod cli;
 pub mod explore;
 pub mod expr;
+pub mod fov;
 pub mod geometric_algebra;
 pub mod geometric_traits;
 pub mod group;
+pub mod image;
 pub mod interval;
 pub mod interval_set;
 pub mod line;
 pub mod line_iterator;
 pub mod linear_index;
+pub mod linear_system;
 pub mod math;
+pub mod matrix;
 pub mod modular;
 pub mod permutations;
+pub mod raster;
+pub mod rational;
 pub mod sketch;
+pub mod sparse_grid;
 pub mod transformations;
 pub mod vector;
 pub mod ord_float;
@@ -1,6 +1,9 @@
 use std::ops::{Add, Mul, Sub};
 
-use super::{line_iterator::LineIterator, vector::Vector};
+use super::{
+    line_iterator::{LineIterator, LineMode},
+    vector::Vector,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Line<T> {
@@ -41,6 +44,9 @@ impl<const C: usize> LineVectori32<C> {
     pub fn iter<const B: bool>(&self) -> LineIterator<B, C> {
         LineIterator::new(self.start, self.end)
     }
+    pub fn iter_with_mode<const B: bool>(&self, mode: LineMode) -> LineIterator<B, C> {
+        LineIterator::with_mode(self.start, self.end, mode)
+    }
 }
 
 pub type LineV2i32 = LineVectori32<2>;
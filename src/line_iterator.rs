@@ -1,140 +1,247 @@
 use super::vector::Vector;
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineMode {
+    // NOTE: One cell per step along the longest axis, the other axes are rounded to the nearest
+    // cell (ties round towards the start).
+    #[default]
+    Bresenham,
+    // NOTE: Consecutive cells differ along a single axis. Where the segment crosses a corner
+    // exactly, the axes are stepped in order.
+    FourConnected,
+    // NOTE: Every cell the segment touches, including both sides of corners it passes through.
+    Supercover,
+}
+
 pub struct LineIterator<const B: bool, const C: usize> {
-    step_options: Vec<Vector<C, i32>>,
+    mode: LineMode,
     at: Vector<C, i32>,
     end: Vector<C, i32>,
+    sign: Vector<C, i32>,
+    delta: [i64; C],
+    // NOTE: Error terms for `Bresenham`, steps taken along each axis for the other modes.
+    progress: [i64; C],
+    // NOTE: Bitmasks of the axes crossed at the next boundary and of the ones already yielded.
+    crossing: Option<(u64, u64)>,
+    started: bool,
 }
 
 impl<const B: bool, const C: usize> LineIterator<B, C> {
     pub fn new(start: Vector<C, i32>, end: Vector<C, i32>) -> Self {
+        Self::with_mode(start, end, LineMode::default())
+    }
+
+    pub fn with_mode(start: Vector<C, i32>, end: Vector<C, i32>, mode: LineMode) -> Self {
+        assert!(C <= 64, "lines are limited to 64 dimensions");
+        let diff = end - start;
         LineIterator {
+            mode,
             at: start,
             end,
-            step_options: vec![],
+            sign: Vector::new(diff.values.map(i32::signum)),
+            delta: diff.values.map(|d| (d as i64).abs()),
+            progress: [0; C],
+            crossing: None,
+            started: false,
         }
     }
-}
-impl<const B: bool, const C: usize> Iterator for LineIterator<B, C> {
-    type Item = Vector<C, i32>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.step_options.is_empty() {
-            if self.at == self.end {
-                self.step_options.push(Vector::all(0));
-                if B {
-                    return Some(self.at);
-                } else {
-                    return None;
-                }
+    pub fn mode(&self) -> LineMode {
+        self.mode
+    }
+
+    fn offset(&self, axes: u64) -> Vector<C, i32> {
+        let mut result = Vector::all(0);
+        for i in 0..C {
+            if axes & (1 << i) != 0 {
+                result.values[i] = self.sign.values[i];
             }
+        }
+        result
+    }
 
-            let mut dir = self.end - self.at;
-            for i in 0..C {
-                if dir.values[i] > 0 {
-                    dir.values[i] = 1;
-                }
-                if dir.values[i] < 0 {
-                    dir.values[i] = -1;
+    fn bresenham_step(&mut self) {
+        let major = (0..C).max_by_key(|i| (self.delta[*i], C - i)).unwrap();
+        self.at.values[major] += self.sign.values[major];
+        for i in 0..C {
+            if i == major {
+                continue;
+            }
+            self.progress[i] += 2 * self.delta[i];
+            if self.progress[i] > self.delta[major] {
+                self.at.values[i] += self.sign.values[i];
+                self.progress[i] -= 2 * self.delta[major];
+            }
+        }
+    }
+
+    // NOTE: The axes whose next cell boundary the segment reaches first. Boundary `k` of axis `i`
+    // is at `t = (2k + 1) / (2 delta_i)`.
+    fn next_crossing(&self) -> u64 {
+        let mut best: Option<usize> = None;
+        let mut axes = 0;
+        for i in 0..C {
+            if self.progress[i] >= self.delta[i] {
+                continue;
+            }
+            let ordering = match best {
+                None => std::cmp::Ordering::Less,
+                Some(j) => ((2 * self.progress[i] + 1) * self.delta[j])
+                    .cmp(&((2 * self.progress[j] + 1) * self.delta[i])),
+            };
+            match ordering {
+                std::cmp::Ordering::Less => {
+                    best = Some(i);
+                    axes = 1 << i;
                 }
+                std::cmp::Ordering::Equal => axes |= 1 << i,
+                std::cmp::Ordering::Greater => (),
             }
+        }
+        match self.mode {
+            LineMode::Supercover => axes,
+            _ => axes & axes.wrapping_neg(),
+        }
+    }
 
+    // NOTE: Walks the subsets of the crossed axes in increasing order, the last one being all of
+    // them, which moves `at` into the next cell.
+    fn crossing_step(&mut self) -> Vector<C, i32> {
+        let (axes, yielded) = match self.crossing {
+            Some(crossing) => crossing,
+            None => (self.next_crossing(), 0),
+        };
+        let subset = yielded.wrapping_sub(axes) & axes;
+        let cell = self.at + self.offset(subset);
+        if subset == axes {
+            self.at = cell;
             for i in 0..C {
-                if dir.values[i] != 0 {
-                    #[allow(clippy::unnecessary_to_owned)]
-                    for mut opt in self.step_options.to_vec() {
-                        debug_assert_eq!(opt.values[i], 0);
-                        opt.values[i] = dir.values[i];
-                        self.step_options.push(opt);
-                    }
-
-                    let mut values = [0; C];
-                    values[i] = dir.values[i];
-                    self.step_options.push(Vector::new(values));
+                if axes & (1 << i) != 0 {
+                    self.progress[i] += 1;
                 }
             }
+            self.crossing = None;
+        } else {
+            self.crossing = Some((axes, subset));
+        }
+        cell
+    }
+}
 
-            Some(self.at)
-        } else if self.at != self.end {
-            let delta = self.end - self.at;
-            let best_step = *self
-                .step_options
-                .iter()
-                .max_by_key(|step| delta.inner(**step))
-                .unwrap();
-            self.at += best_step;
+impl<const B: bool, const C: usize> Iterator for LineIterator<B, C> {
+    type Item = Vector<C, i32>;
 
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
             if !B && self.at == self.end {
                 return None;
             }
+            return Some(self.at);
+        }
+        if self.at == self.end {
+            return None;
+        }
 
-            Some(self.at)
-        } else {
-            None
+        let cell = match self.mode {
+            LineMode::Bresenham => {
+                self.bresenham_step();
+                self.at
+            }
+            LineMode::FourConnected | LineMode::Supercover => self.crossing_step(),
+        };
+
+        if !B && cell == self.end {
+            return None;
         }
+        Some(cell)
     }
 }
 
-// pub type LineIterator2 = LineIterator<2>;
-// pub type LineIterator3 = LineIterator<3>;
-
-// impl<const C: usize> Iterator for LineIterator<C> {
-//     type Item = Vector<C, i32>;
-//
-//     fn next(&mut self) -> Option<Self::Item> {
-//         if self.step_options.is_empty() {
-//             let dir = self.end - self.at;
-//             match dir.x().cmp(&0) {
-//                 std::cmp::Ordering::Less => {
-//                     self.step_options.push(V2::from_xy(-1, 0));
-//                     match dir.y().cmp(&0) {
-//                         std::cmp::Ordering::Less => {
-//                             self.step_options.push(V2::from_xy(0, -1));
-//                             self.step_options.push(V2::from_xy(-1, -1));
-//                         }
-//                         std::cmp::Ordering::Equal => (),
-//                         std::cmp::Ordering::Greater => {
-//                             self.step_options.push(V2::from_xy(0, 1));
-//                             self.step_options.push(V2::from_xy(-1, 1));
-//                         }
-//                     };
-//                 }
-//                 std::cmp::Ordering::Equal => {
-//                     match dir.y().cmp(&0) {
-//                         std::cmp::Ordering::Less => self.step_options.push(V2::from_xy(0, -1)),
-//                         std::cmp::Ordering::Equal => (),
-//                         std::cmp::Ordering::Greater => self.step_options.push(V2::from_xy(0, 1)),
-//                     };
-//                 }
-//                 std::cmp::Ordering::Greater => {
-//                     self.step_options.push(V2::from_xy(1, 0));
-//                     match dir.y().cmp(&0) {
-//                         std::cmp::Ordering::Less => {
-//                             self.step_options.push(V2::from_xy(0, -1));
-//                             self.step_options.push(V2::from_xy(1, -1));
-//                         }
-//                         std::cmp::Ordering::Equal => (),
-//                         std::cmp::Ordering::Greater => {
-//                             self.step_options.push(V2::from_xy(0, 1));
-//                             self.step_options.push(V2::from_xy(1, 1));
-//                         }
-//                     };
-//                 }
-//             }
-//
-//             Some(self.at)
-//         } else if self.at != self.end {
-//             let delta = self.end - self.at;
-//             let best_step = *self
-//                 .step_options
-//                 .iter()
-//                 .max_by_key(|step| delta.inner(**step))
-//                 .unwrap();
-//             self.at += best_step;
-//
-//             Some(self.at)
-//         } else {
-//             None
-//         }
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::{LineIterator, LineMode};
+    use crate::vector::{V2i32, V3};
+
+    fn cells(from: (i32, i32), to: (i32, i32), mode: LineMode) -> Vec<(i32, i32)> {
+        LineIterator::<true, 2>::with_mode(
+            V2i32::from_xy(from.0, from.1),
+            V2i32::from_xy(to.0, to.1),
+            mode,
+        )
+        .map(|p| (p.x(), p.y()))
+        .collect()
+    }
+
+    #[test]
+    fn bresenham() {
+        assert_eq!(
+            cells((0, 0), (3, 1), LineMode::Bresenham),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            cells((0, 0), (-2, 5), LineMode::Bresenham),
+            vec![(0, 0), (0, 1), (-1, 2), (-1, 3), (-2, 4), (-2, 5)]
+        );
+        assert_eq!(cells((2, 2), (2, 2), LineMode::Bresenham), vec![(2, 2)]);
+
+        let line: Vec<_> =
+            LineIterator::<true, 3>::new(V3::from_xyz(0, 0, 0), V3::from_xyz(4, 2, -1)).collect();
+        assert_eq!(line.len(), 5);
+        assert_eq!(line[4], V3::from_xyz(4, 2, -1));
+        for pair in line.windows(2) {
+            let step = pair[1] - pair[0];
+            assert!(step.values.iter().all(|d| d.abs() <= 1));
+            assert_eq!(step.x(), 1);
+        }
+    }
+
+    #[test]
+    fn four_connected_and_supercover() {
+        assert_eq!(
+            cells((0, 0), (3, 1), LineMode::FourConnected),
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            cells((0, 0), (2, 2), LineMode::FourConnected),
+            vec![(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)]
+        );
+        assert_eq!(
+            cells((0, 0), (2, 2), LineMode::Supercover),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            cells((0, 0), (3, 1), LineMode::Supercover),
+            vec![(0, 0), (1, 0), (2, 0), (1, 1), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            cells((0, 0), (3, 2), LineMode::Supercover),
+            cells((0, 0), (3, 2), LineMode::FourConnected)
+        );
+
+        let corner: Vec<_> =
+            LineIterator::<true, 3>::with_mode(V3::all(0), V3::all(1), LineMode::Supercover)
+                .collect();
+        assert_eq!(corner.len(), 8);
+    }
+
+    #[test]
+    fn endpoint_inclusion() {
+        for mode in [
+            LineMode::Bresenham,
+            LineMode::FourConnected,
+            LineMode::Supercover,
+        ] {
+            let a = V2i32::from_xy(0, 0);
+            let b = V2i32::from_xy(-3, 2);
+            let with_end: Vec<_> = LineIterator::<true, 2>::with_mode(a, b, mode).collect();
+            let without_end: Vec<_> = LineIterator::<false, 2>::with_mode(a, b, mode).collect();
+            assert_eq!(with_end.first(), Some(&a));
+            assert_eq!(with_end.last(), Some(&b));
+            assert_eq!(without_end, with_end[..with_end.len() - 1]);
+
+            assert_eq!(LineIterator::<false, 2>::with_mode(a, a, mode).count(), 0);
+            assert_eq!(LineIterator::<true, 2>::with_mode(a, a, mode).count(), 1);
+        }
+    }
+}
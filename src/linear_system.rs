@@ -0,0 +1,444 @@
+use super::{
+    math::{Field, Float},
+    modular::{Modi32, Modusize},
+    rational::Rational,
+};
+
+// NOTE: How elimination treats the entries of a field. Exact fields take the first non-zero
+// pivot, floats the one with the largest magnitude and ignore rounding noise.
+pub trait EliminationField: Field {
+    // NOTE: What `is_negligible` may ignore in a column that started out with `column`.
+    fn tolerance<'a>(_column: impl ExactSizeIterator<Item = &'a Self>) -> Self
+    where
+        Self: 'a,
+    {
+        Self::zero()
+    }
+    fn is_negligible(&self, _tolerance: &Self) -> bool {
+        *self == Self::zero()
+    }
+    // NOTE: Whether the non-negligible `self` can be a pivot. Always true in a field, but
+    // integers modulo a composite number also have non-zero values without an inverse.
+    fn is_invertible(&self) -> bool {
+        true
+    }
+    // NOTE: Whether `self` is a better pivot than the non-negligible `other`.
+    fn is_better_pivot(&self, _other: &Self) -> bool {
+        false
+    }
+}
+
+impl EliminationField for Rational {}
+impl<const C: i32> EliminationField for Modi32<C> {
+    fn is_invertible(&self) -> bool {
+        self.inverse().is_some()
+    }
+}
+impl<const C: usize> EliminationField for Modusize<C> {
+    fn is_invertible(&self) -> bool {
+        self.inverse().is_some()
+    }
+}
+
+impl<T: Float> EliminationField for T {
+    // NOTE: Rounding noise grows with the largest entry and the number of rows combined.
+    fn tolerance<'a>(column: impl ExactSizeIterator<Item = &'a Self>) -> Self
+    where
+        Self: 'a,
+    {
+        let n = (0..column.len()).fold(T::zero(), |n, _| n + T::one());
+        let largest = column.fold(T::zero(), |max, x| match x.abs() > max {
+            true => x.abs(),
+            false => max,
+        });
+        largest * T::EPSILON * n
+    }
+    fn is_negligible(&self, tolerance: &Self) -> bool {
+        self.abs() <= *tolerance
+    }
+    fn is_better_pivot(&self, other: &Self) -> bool {
+        self.abs() > other.abs()
+    }
+}
+
+// NOTE: Reduced row echelon form of a matrix given row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowEchelon<T> {
+    pub rows: Vec<Vec<T>>,
+    // NOTE: Pivot column of every non-zero row, the other rows are all zero.
+    pub pivots: Vec<usize>,
+    pub columns: usize,
+}
+
+impl<T: EliminationField> RowEchelon<T> {
+    pub fn new(mut rows: Vec<Vec<T>>, columns: usize) -> Self {
+        assert!(
+            rows.iter().all(|row| row.len() == columns),
+            "all rows need {columns} columns"
+        );
+        let pivots = reduce(&mut rows, columns);
+        Self {
+            rows,
+            pivots,
+            columns,
+        }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    // NOTE: Basis of `{x : Ax = 0}`, one vector per column without a pivot.
+    pub fn nullspace(&self) -> Vec<Vec<T>> {
+        nullspace_basis(&self.rows, &self.pivots, self.columns)
+    }
+}
+
+// NOTE: Gauss-Jordan elimination of the first `columns` columns, returns the pivot columns.
+fn reduce<T: EliminationField>(rows: &mut [Vec<T>], columns: usize) -> Vec<usize> {
+    let tolerances: Vec<T> = (0..columns)
+        .map(|column| T::tolerance(rows.iter().map(|row| &row[column])))
+        .collect();
+    let mut pivots = vec![];
+    for column in 0..columns {
+        let (r, tolerance) = (pivots.len(), &tolerances[column]);
+        let mut best: Option<usize> = None;
+        for i in r..rows.len() {
+            let v = rows[i][column];
+            if v.is_negligible(tolerance) || !v.is_invertible() {
+                continue;
+            }
+            let better = match best {
+                Some(b) => v.is_better_pivot(&rows[b][column]),
+                None => true,
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        let Some(best) = best else {
+            continue;
+        };
+        rows.swap(r, best);
+
+        let scale = T::one() / rows[r][column];
+        for v in rows[r].iter_mut() {
+            *v = *v * scale;
+        }
+        for i in 0..rows.len() {
+            let factor = rows[i][column];
+            if i == r || factor.is_negligible(tolerance) {
+                continue;
+            }
+            for j in 0..rows[i].len() {
+                let pivot_value = rows[r][j];
+                rows[i][j] = rows[i][j] - factor * pivot_value;
+            }
+        }
+        pivots.push(column);
+    }
+    pivots
+}
+
+fn nullspace_basis<T: Field>(rows: &[Vec<T>], pivots: &[usize], columns: usize) -> Vec<Vec<T>> {
+    (0..columns)
+        .filter(|c| !pivots.contains(c))
+        .map(|free| {
+            let mut x = vec![T::zero(); columns];
+            x[free] = T::one();
+            for (row, pivot) in rows.iter().zip(pivots) {
+                x[*pivot] = -row[free];
+            }
+            x
+        })
+        .collect()
+}
+
+// NOTE: All solutions of `Ax = b`: `particular` plus any linear combination of `nullspace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearSolution<T> {
+    pub rank: usize,
+    // NOTE: `None` when the system is inconsistent.
+    pub particular: Option<Vec<T>>,
+    pub nullspace: Vec<Vec<T>>,
+}
+
+impl<T> LinearSolution<T> {
+    pub fn is_consistent(&self) -> bool {
+        self.particular.is_some()
+    }
+    pub fn is_unique(&self) -> bool {
+        self.is_consistent() && self.nullspace.is_empty()
+    }
+    // NOTE: The only solution, if there is exactly one.
+    pub fn unique(&self) -> Option<&[T]> {
+        if self.is_unique() {
+            self.particular.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+// NOTE: `a` is given row by row, every row as long as `columns`, and `b` has one entry per row.
+// With a composite modulus only invertible values become pivots. That avoids dividing by zero
+// divisors, but then the rank and solution set are only exact when no such value was skipped.
+pub fn solve<T: EliminationField>(a: &[Vec<T>], b: &[T], columns: usize) -> LinearSolution<T> {
+    assert_eq!(a.len(), b.len(), "one right-hand side value per row");
+    let augmented: Vec<Vec<T>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, v)| {
+            assert_eq!(row.len(), columns, "all rows need {columns} columns");
+            row.iter().copied().chain([*v]).collect()
+        })
+        .collect();
+    let echelon = RowEchelon::new(augmented, columns + 1);
+    let (rows, pivots) = (&echelon.rows, &echelon.pivots);
+
+    let rank = pivots.iter().filter(|p| **p < columns).count();
+    let particular = (rank == pivots.len()).then(|| {
+        let mut x = vec![T::zero(); columns];
+        for (row, pivot) in rows.iter().zip(pivots) {
+            x[*pivot] = row[columns];
+        }
+        x
+    });
+    LinearSolution {
+        rank,
+        particular,
+        nullspace: nullspace_basis(rows, &pivots[..rank], columns),
+    }
+}
+
+// NOTE: Row of GF(2) coefficients packed 64 per word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitRow(Vec<u64>);
+
+impl BitRow {
+    fn new(bits: usize) -> Self {
+        Self(vec![0; bits.div_ceil(64)])
+    }
+    fn get(&self, i: usize) -> bool {
+        self.0[i / 64] >> (i % 64) & 1 == 1
+    }
+    fn set(&mut self, i: usize, v: bool) {
+        if v {
+            self.0[i / 64] |= 1 << (i % 64);
+        } else {
+            self.0[i / 64] &= !(1 << (i % 64));
+        }
+    }
+    fn xor(&mut self, rhs: &Self) {
+        for (a, b) in self.0.iter_mut().zip(&rhs.0) {
+            *a ^= b;
+        }
+    }
+}
+
+// NOTE: Same as `solve` over GF(2), where addition is xor. Rows are bitsets, so large systems
+// like lights-out puzzles stay cheap.
+pub fn solve_gf2(a: &[Vec<bool>], b: &[bool], columns: usize) -> LinearSolution<bool> {
+    assert_eq!(a.len(), b.len(), "one right-hand side value per row");
+    let mut rows: Vec<BitRow> = a
+        .iter()
+        .zip(b)
+        .map(|(row, v)| {
+            assert_eq!(row.len(), columns, "all rows need {columns} columns");
+            let mut bits = BitRow::new(columns + 1);
+            for (i, x) in row.iter().chain([v]).enumerate() {
+                bits.set(i, *x);
+            }
+            bits
+        })
+        .collect();
+
+    let mut pivots = vec![];
+    for column in 0..=columns {
+        let r = pivots.len();
+        let Some(found) = (r..rows.len()).find(|i| rows[*i].get(column)) else {
+            continue;
+        };
+        rows.swap(r, found);
+        let pivot = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r && row.get(column) {
+                row.xor(&pivot);
+            }
+        }
+        pivots.push(column);
+    }
+
+    let rank = pivots.iter().filter(|p| **p < columns).count();
+    let particular = (rank == pivots.len()).then(|| {
+        let mut x = vec![false; columns];
+        for (row, pivot) in rows.iter().zip(&pivots) {
+            x[*pivot] = row.get(columns);
+        }
+        x
+    });
+    let nullspace = (0..columns)
+        .filter(|c| !pivots.contains(c))
+        .map(|free| {
+            let mut x = vec![false; columns];
+            x[free] = true;
+            for (row, pivot) in rows.iter().zip(&pivots[..rank]) {
+                x[*pivot] = row.get(free);
+            }
+            x
+        })
+        .collect();
+    LinearSolution {
+        rank,
+        particular,
+        nullspace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve_gf2, RowEchelon};
+    use crate::{
+        modular::{Modi32, Modusize},
+        rational::Rational,
+    };
+
+    fn rationals(rows: &[&[i128]]) -> Vec<Vec<Rational>> {
+        rows.iter()
+            .map(|row| row.iter().map(|x| Rational::integer(*x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn rational_systems() {
+        let a = rationals(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+        let b = [8, -11, -3].map(Rational::integer);
+        let solution = solve(&a, &b, 3);
+        assert_eq!(solution.rank, 3);
+        assert_eq!(
+            solution.unique(),
+            Some(&[2, 3, -1].map(Rational::integer)[..])
+        );
+
+        let a = rationals(&[&[1, 2, 3], &[2, 4, 6]]);
+        let solution = solve(&a, &[Rational::integer(6), Rational::integer(12)], 3);
+        assert_eq!(solution.rank, 1);
+        assert_eq!(
+            solution.particular,
+            Some([6, 0, 0].map(Rational::integer).to_vec())
+        );
+        assert_eq!(solution.nullspace, rationals(&[&[-2, 1, 0], &[-3, 0, 1]]));
+        assert!(!solution.is_unique());
+
+        let inconsistent = solve(&a, &[Rational::integer(1), Rational::integer(3)], 3);
+        assert!(!inconsistent.is_consistent());
+        assert_eq!(inconsistent.rank, 1);
+
+        let echelon = RowEchelon::new(rationals(&[&[0, 3], &[0, 1]]), 2);
+        assert_eq!(echelon.rank(), 1);
+        assert_eq!(echelon.nullspace(), rationals(&[&[1, 0]]));
+    }
+
+    #[test]
+    fn large_rational_systems() {
+        // NOTE: Positions and velocities of the size found in hailstone puzzles, where positions
+        // are around `1e14`.
+        let a = rationals(&[
+            &[92, 296, 222871208630164, 195349107604949],
+            &[-146, -10, -107158705214365, 165075777565826],
+            &[229, -128, -376582829262678, -271475202860073],
+            &[-213, -40, -141937372181626, -277984362704601],
+        ]);
+        let b = [
+            155665456214468277,
+            -23358541332835247,
+            20293734457681364,
+            -88888054309381854,
+        ]
+        .map(Rational::integer);
+        let solution = solve(&a, &b, 4);
+        assert_eq!(
+            solution.unique(),
+            Some(&[287430900705823, 451620998712421, -91, 81].map(Rational::integer)[..])
+        );
+    }
+
+    #[test]
+    fn modular_systems() {
+        type F = Modi32<7>;
+        let a: Vec<Vec<F>> = [[1, 2], [3, 4]]
+            .iter()
+            .map(|row| row.iter().map(|x| F::new(*x)).collect())
+            .collect();
+        let solution = solve(&a, &[F::new(5), F::new(6)], 2);
+        let x = solution.unique().unwrap();
+        assert_eq!(x[0] + F::new(2) * x[1], F::new(5));
+        assert_eq!(F::new(3) * x[0] + F::new(4) * x[1], F::new(6));
+
+        // NOTE: 2 has no inverse modulo 12, so the pivot of the first column is the 1 below it.
+        type Z = Modusize<12>;
+        let a = vec![vec![Z::new(2), Z::new(1)], vec![Z::new(1), Z::new(1)]];
+        let solution = solve(&a, &[Z::new(5), Z::new(7)], 2);
+        let x = solution.unique().unwrap();
+        assert_eq!(Z::new(2) * x[0] + x[1], Z::new(5));
+        assert_eq!(x[0] + x[1], Z::new(7));
+    }
+
+    #[test]
+    fn float_systems() {
+        let a = vec![vec![1e-12, 1.0], vec![1.0, 1.0f64]];
+        let solution = solve(&a, &[1.0, 2.0], 2);
+        let x = solution.unique().unwrap();
+        assert!((x[0] - 1.0).abs() < 1e-9 && (x[1] - 1.0).abs() < 1e-9);
+
+        let singular = solve(&[vec![1.0, 2.0], vec![0.5, 1.0]], &[3.0, 1.5], 2);
+        assert_eq!(singular.rank, 1);
+        assert_eq!(singular.nullspace, vec![vec![-2.0, 1.0]]);
+
+        // NOTE: What counts as rounding noise depends on the size of the entries.
+        let (r1, r2) = ([123456789.123, -98765432.1], [314159265.3, 271828182.8]);
+        let r3 = [r1[0] + r2[0], r1[1] + r2[1]];
+        let (b1, b2) = (987654321.9, -123123123.7);
+        let large = solve(
+            &[r1.to_vec(), r2.to_vec(), r3.to_vec()],
+            &[b1, b2, b1 + b2],
+            2,
+        );
+        assert!(large.is_consistent());
+        assert_eq!(large.rank, 2);
+        let tiny = RowEchelon::new(vec![vec![1e-10, 0.0], vec![0.0, 1e-10]], 2);
+        assert_eq!(tiny.rank(), 2);
+    }
+
+    #[test]
+    fn lights_out() {
+        // NOTE: 3x3 lights-out, pressing a light toggles it and its orthogonal neighbours.
+        let size = 3;
+        let a: Vec<Vec<bool>> = (0..size * size)
+            .map(|cell| {
+                let (x, y) = ((cell % size) as i32, (cell / size) as i32);
+                (0..size * size)
+                    .map(|press| {
+                        let (px, py) = ((press % size) as i32, (press / size) as i32);
+                        (x - px).abs() + (y - py).abs() <= 1
+                    })
+                    .collect()
+            })
+            .collect();
+        let lit = vec![true; size * size];
+        let solution = solve_gf2(&a, &lit, size * size);
+        assert_eq!(solution.rank, 9);
+        let presses = solution.unique().unwrap();
+        for (row, target) in a.iter().zip(&lit) {
+            let toggled = row.iter().zip(presses).filter(|(a, p)| **a && **p).count();
+            assert_eq!(toggled % 2 == 1, *target);
+        }
+
+        let a = vec![vec![true, true, false], vec![false, true, true]];
+        let solution = solve_gf2(&a, &[true, false], 3);
+        assert_eq!(solution.rank, 2);
+        assert_eq!(solution.particular, Some(vec![true, false, false]));
+        assert_eq!(solution.nullspace, vec![vec![true, true, true]]);
+        assert!(!solve_gf2(&[vec![true], vec![true]], &[true, false], 1).is_consistent());
+    }
+}
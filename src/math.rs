@@ -1,3 +1,5 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
 pub trait Zero {
     fn zero() -> Self;
 }
@@ -31,6 +33,80 @@ macro_rules! one {
 zero!(isize, i8, i16, i32, i64, i128, usize, u8, u16, u32, u64, u128, f32, f64);
 one!(isize, i8, i16, i32, i64, i128, usize, u8, u16, u32, u64, u128, f32, f64);
 
+// NOTE: Numbers with exact (or, for floats, best-effort) division by anything but zero.
+pub trait Field:
+    Copy
+    + PartialEq
+    + Zero
+    + One
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+}
+
+pub trait Float: Field + PartialOrd {
+    const EPSILON: Self;
+
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, x: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> std::cmp::Ordering;
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        if self < min {
+            min
+        } else if self > max {
+            max
+        } else {
+            self
+        }
+    }
+    fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+        (self - other).abs() <= tolerance
+    }
+}
+
+macro_rules! float {
+    ($($t:ident),*) => {
+        $(
+        impl Field for $t {}
+
+        impl Float for $t {
+            const EPSILON: Self = $t::EPSILON;
+
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+            fn acos(self) -> Self {
+                $t::acos(self)
+            }
+            fn atan2(self, x: Self) -> Self {
+                $t::atan2(self, x)
+            }
+            fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+                $t::total_cmp(self, other)
+            }
+        })*
+    };
+}
+
+float!(f32, f64);
+
 pub trait Gcd {
     fn gcd(a: Self, b: Self) -> Self;
     fn lcm(a: Self, b: Self) -> Self;
@@ -61,7 +137,7 @@ macro_rules! gcd {
     };
 }
 
-gcd!(usize, i32, i64);
+gcd!(usize, i32, i64, i128, u128);
 
 pub trait AbsoluteValue
 where
@@ -119,6 +195,14 @@ pub fn triangle_numbers(n: i32) -> i32 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn float_helpers() {
+        assert_eq!(Float::clamp(1.5f32, 0.0, 1.0), 1.0);
+        assert_eq!(Float::clamp(-2.0f64, -1.0, 1.0), -1.0);
+        assert!(0.1f64.approx_eq(0.3 - 0.2, 1e-9));
+        assert!(!0.1f32.approx_eq(0.2, 0.05));
+    }
+
     #[test]
     fn test_triangle_numbers() {
         assert_eq!(triangle_numbers(1), 1);
@@ -0,0 +1,506 @@
+use std::{
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use super::{
+    linear_system::{EliminationField, RowEchelon},
+    math::{Float, One, Zero},
+    rational::Rational,
+    transformations::Transform,
+    vector::{Vector, V2, V3},
+};
+
+// NOTE: `R` rows by `C` columns, stored row by row. Vectors are columns, `m * v` transforms `v`.
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matrix<const R: usize, const C: usize, T> {
+    pub values: [[T; C]; R],
+}
+
+pub type Matrix2<T> = Matrix<2, 2, T>;
+pub type Matrix3<T> = Matrix<3, 3, T>;
+pub type Matrix4<T> = Matrix<4, 4, T>;
+
+impl<const R: usize, const C: usize, T> Matrix<R, C, T> {
+    pub const fn new(values: [[T; C]; R]) -> Self {
+        Self { values }
+    }
+}
+
+impl<const R: usize, const C: usize, T: Copy> Matrix<R, C, T> {
+    pub fn all(value: T) -> Self {
+        Self::new([[value; C]; R])
+    }
+
+    pub fn from_rows(rows: [Vector<C, T>; R]) -> Self {
+        Self::new(rows.map(|row| row.values))
+    }
+    pub fn from_columns(columns: [Vector<R, T>; C]) -> Self {
+        Matrix::new(columns.map(|column| column.values)).transposed()
+    }
+
+    pub fn row(&self, i: usize) -> Vector<C, T> {
+        Vector::new(self.values[i])
+    }
+    pub fn column(&self, j: usize) -> Vector<R, T> {
+        Vector::new(std::array::from_fn(|i| self.values[i][j]))
+    }
+    pub fn set_row(&mut self, i: usize, row: Vector<C, T>) {
+        self.values[i] = row.values;
+    }
+    pub fn set_column(&mut self, j: usize, column: Vector<R, T>) {
+        for i in 0..R {
+            self.values[i][j] = column.values[i];
+        }
+    }
+    pub fn rows(&self) -> impl Iterator<Item = Vector<C, T>> + '_ {
+        self.values.iter().map(|row| Vector::new(*row))
+    }
+    pub fn columns(&self) -> impl Iterator<Item = Vector<R, T>> + '_ {
+        (0..C).map(|j| self.column(j))
+    }
+
+    pub fn transposed(&self) -> Matrix<C, R, T> {
+        Matrix::new(std::array::from_fn(|j| {
+            std::array::from_fn(|i| self.values[i][j])
+        }))
+    }
+
+    pub fn map<U, F: Fn(T) -> U>(&self, f: F) -> Matrix<R, C, U> {
+        Matrix::new(self.values.map(|row| row.map(&f)))
+    }
+}
+
+impl<const N: usize, T: Copy + Zero + One> Matrix<N, N, T> {
+    pub fn identity() -> Self {
+        Self::from_diagonal(Vector::all(T::one()))
+    }
+    pub fn from_diagonal(diagonal: Vector<N, T>) -> Self {
+        let mut result = Self::all(T::zero());
+        for i in 0..N {
+            result.values[i][i] = diagonal.values[i];
+        }
+        result
+    }
+}
+
+impl<const N: usize, T> Matrix<N, N, T>
+where
+    T: Copy + PartialEq + Zero + One,
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + Neg<Output = T>,
+{
+    // NOTE: Fraction-free Bareiss elimination, every division is exact, so integer matrices
+    // only ever see integer intermediates. Needs a type without zero divisors, integers modulo a
+    // composite number can panic dividing by a non-invertible pivot.
+    pub fn determinant(&self) -> T {
+        if N == 0 {
+            return T::one();
+        }
+        let mut m = self.values;
+        let mut sign = T::one();
+        let mut previous = T::one();
+        for k in 0..N - 1 {
+            if m[k][k] == T::zero() {
+                let Some(pivot) = (k + 1..N).find(|i| m[*i][k] != T::zero()) else {
+                    return T::zero();
+                };
+                m.swap(k, pivot);
+                sign = -sign;
+            }
+            for i in k + 1..N {
+                for j in k + 1..N {
+                    m[i][j] = (m[i][j] * m[k][k] - m[i][k] * m[k][j]) / previous;
+                }
+            }
+            previous = m[k][k];
+        }
+        sign * m[N - 1][N - 1]
+    }
+}
+
+// NOTE: Gauss-Jordan elimination of `[matrix | identity]`, `None` when the matrix is singular.
+fn gauss_jordan_inverse<const N: usize, T>(matrix: &Matrix<N, N, T>) -> Option<Matrix<N, N, T>>
+where
+    T: EliminationField,
+{
+    let identity = Matrix::<N, N, T>::identity();
+    let rows = (0..N)
+        .map(|i| {
+            matrix.values[i]
+                .iter()
+                .chain(&identity.values[i])
+                .copied()
+                .collect()
+        })
+        .collect();
+    let echelon = RowEchelon::new(rows, 2 * N);
+    if echelon.pivots.iter().take_while(|p| **p < N).count() < N {
+        return None;
+    }
+    let mut result = identity;
+    for (row, values) in result.values.iter_mut().zip(&echelon.rows) {
+        row.copy_from_slice(&values[N..]);
+    }
+    Some(result)
+}
+
+impl<const N: usize, T: EliminationField> Matrix<N, N, T> {
+    // NOTE: For exact fields like `Rational`. With a composite modulus only invertible values
+    // become pivots, so a matrix whose columns have no invertible entry counts as singular.
+    pub fn inverse_exact(&self) -> Option<Self> {
+        gauss_jordan_inverse(self)
+    }
+}
+
+impl<const N: usize, T: Float> Matrix<N, N, T> {
+    // NOTE: Partial pivoting, entries within rounding error of the largest one in their column
+    // count as zero, so scaling a matrix does not change whether it is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        gauss_jordan_inverse(self)
+    }
+}
+
+impl<const N: usize, T> Matrix<N, N, T>
+where
+    T: Copy + Into<i128> + TryFrom<i128>,
+{
+    // NOTE: Only exists when the determinant is `1` or `-1`, computed exactly over `Rational`.
+    pub fn inverse_integer(&self) -> Option<Self> {
+        let inverse = self.map(|x| Rational::integer(x.into())).inverse_exact()?;
+        let mut result = *self;
+        for i in 0..N {
+            for j in 0..N {
+                result.values[i][j] = T::try_from(inverse.values[i][j].to_integer()?).ok()?;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl<const R: usize, const C: usize, T> std::ops::Index<(usize, usize)> for Matrix<R, C, T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        &self.values[i][j]
+    }
+}
+impl<const R: usize, const C: usize, T> std::ops::IndexMut<(usize, usize)> for Matrix<R, C, T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        &mut self.values[i][j]
+    }
+}
+
+impl<const R: usize, const C: usize, T: Add<Output = T> + Copy> Add for Matrix<R, C, T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| self.values[i][j] + rhs.values[i][j])
+        }))
+    }
+}
+impl<const R: usize, const C: usize, T: Sub<Output = T> + Copy> Sub for Matrix<R, C, T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| self.values[i][j] - rhs.values[i][j])
+        }))
+    }
+}
+impl<const R: usize, const C: usize, T: Mul<Output = T> + Copy> Mul<T> for Matrix<R, C, T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+
+impl<const R: usize, const C: usize, T> Mul<Vector<C, T>> for Matrix<R, C, T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Zero,
+{
+    type Output = Vector<R, T>;
+
+    fn mul(self, rhs: Vector<C, T>) -> Self::Output {
+        Vector::new(std::array::from_fn(|i| {
+            (0..C).fold(T::zero(), |acc, j| acc + self.values[i][j] * rhs.values[j])
+        }))
+    }
+}
+
+impl<const R: usize, const K: usize, const C: usize, T> Mul<Matrix<K, C, T>> for Matrix<R, K, T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Zero,
+{
+    type Output = Matrix<R, C, T>;
+
+    fn mul(self, rhs: Matrix<K, C, T>) -> Self::Output {
+        Matrix::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                (0..K).fold(T::zero(), |acc, k| {
+                    acc + self.values[i][k] * rhs.values[k][j]
+                })
+            })
+        }))
+    }
+}
+
+impl<const R: usize, const C: usize, T: Zero + Copy> Zero for Matrix<R, C, T> {
+    fn zero() -> Self {
+        Self::all(T::zero())
+    }
+}
+impl<const N: usize, T: Zero + One + Copy> One for Matrix<N, N, T> {
+    fn one() -> Self {
+        Self::identity()
+    }
+}
+
+impl<const R: usize, const C: usize, T: Display> Display for Matrix<R, C, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.values {
+            for (j, v) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{v}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// NOTE: A matrix together with its inverse, computed once when it is built. Only these
+// implement `Transform`, a singular matrix has no `inverse_transform`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvertibleMatrix<const N: usize, T> {
+    matrix: Matrix<N, N, T>,
+    inverse: Matrix<N, N, T>,
+}
+
+impl<const N: usize, T: Float> InvertibleMatrix<N, T> {
+    // NOTE: `None` if `matrix` is singular.
+    pub fn new(matrix: Matrix<N, N, T>) -> Option<Self> {
+        Some(Self {
+            matrix,
+            inverse: matrix.inverse()?,
+        })
+    }
+}
+
+impl<const N: usize, T> InvertibleMatrix<N, T> {
+    pub fn matrix(&self) -> &Matrix<N, N, T> {
+        &self.matrix
+    }
+    pub fn inverse(&self) -> &Matrix<N, N, T> {
+        &self.inverse
+    }
+    pub fn inverted(self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+}
+
+// NOTE: Linear maps.
+impl<const N: usize, T: Float> Transform<Vector<N, T>> for InvertibleMatrix<N, T> {
+    fn transform(&self, object: Vector<N, T>) -> Vector<N, T> {
+        self.matrix * object
+    }
+
+    fn inverse_transform(&self, object: Vector<N, T>) -> Vector<N, T> {
+        self.inverse * object
+    }
+}
+
+// NOTE: Affine maps of points in homogeneous coordinates, composed by multiplying the matrices.
+// The last row is expected to be `0 .. 0 1`.
+impl<T: Float> Matrix3<T> {
+    pub fn from_translation(translation: V2<T>) -> Self {
+        let mut result = Self::identity();
+        result.values[0][2] = translation.x();
+        result.values[1][2] = translation.y();
+        result
+    }
+}
+impl<T: Float> Matrix4<T> {
+    pub fn from_translation(translation: V3<T>) -> Self {
+        let mut result = Self::identity();
+        result.values[0][3] = translation.x();
+        result.values[1][3] = translation.y();
+        result.values[2][3] = translation.z();
+        result
+    }
+}
+
+impl<T: Float> Matrix3<T> {
+    pub fn transform_point(&self, point: V2<T>) -> V2<T> {
+        let [x, y, _] = (*self * Vector::new([point.x(), point.y(), T::one()])).values;
+        V2::from_xy(x, y)
+    }
+}
+impl<T: Float> Matrix4<T> {
+    pub fn transform_point(&self, point: V3<T>) -> V3<T> {
+        let [x, y, z, _] =
+            (*self * Vector::new([point.x(), point.y(), point.z(), T::one()])).values;
+        V3::from_xyz(x, y, z)
+    }
+}
+
+impl<T: Float> Transform<V2<T>> for InvertibleMatrix<3, T> {
+    fn transform(&self, object: V2<T>) -> V2<T> {
+        self.matrix.transform_point(object)
+    }
+
+    fn inverse_transform(&self, object: V2<T>) -> V2<T> {
+        self.inverse.transform_point(object)
+    }
+}
+impl<T: Float> Transform<V3<T>> for InvertibleMatrix<4, T> {
+    fn transform(&self, object: V3<T>) -> V3<T> {
+        self.matrix.transform_point(object)
+    }
+
+    fn inverse_transform(&self, object: V3<T>) -> V3<T> {
+        self.inverse.transform_point(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvertibleMatrix, Matrix, Matrix2, Matrix3};
+    use crate::{
+        modular::Modi32,
+        rational::Rational,
+        transformations::Transform,
+        vector::{Vector, V2, V3},
+    };
+
+    #[test]
+    fn access_and_products() {
+        let m = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(m.row(1), V3::from_xyz(4, 5, 6));
+        assert_eq!(m.column(2), V2::from_xy(3, 6));
+        assert_eq!(m[(0, 1)], 2);
+        assert_eq!(m.transposed(), Matrix::new([[1, 4], [2, 5], [3, 6]]));
+        assert_eq!(
+            Matrix::from_columns(m.columns().collect::<Vec<_>>().try_into().unwrap()),
+            m
+        );
+        assert_eq!(Matrix::from_rows([m.row(0), m.row(1)]), m);
+
+        assert_eq!(m * V3::from_xyz(1, 0, -1), V2::from_xy(-2, -2));
+        assert_eq!(m * m.transposed(), Matrix::new([[14, 32], [32, 77]]));
+        assert_eq!(Matrix2::identity() * m, m);
+        assert_eq!(m + m, m * 2);
+        assert_eq!(m.to_string(), "1 2 3\n4 5 6\n");
+
+        let mut n = m;
+        n.set_column(0, V2::from_xy(0, 0));
+        n[(1, 2)] = 9;
+        assert_eq!(n, Matrix::new([[0, 2, 3], [0, 5, 9]]));
+    }
+
+    #[test]
+    fn determinants() {
+        assert_eq!(Matrix::new([[2, 0], [1, 3]]).determinant(), 6);
+        assert_eq!(
+            Matrix::new([[0, 1, 2], [3, 4, 5], [6, 7, 9]]).determinant(),
+            -3
+        );
+        assert_eq!(
+            Matrix::new([[1, 2, 3], [4, 5, 6], [7, 8, 9]]).determinant(),
+            0
+        );
+        let m = Matrix::new([[2, -3, 1, 5], [4, 0, -2, 1], [-1, 6, 3, 2], [3, 1, 1, -4]]);
+        assert_eq!(m.determinant(), -681);
+        assert_eq!(m.map(|x| x as f64).determinant().round(), -681.0);
+        assert_eq!(Matrix::<0, 0, i32>::new([]).determinant(), 1);
+    }
+
+    #[test]
+    fn inverses() {
+        let m = Matrix::new([[0.0, 2.0], [1.0, 4.0f64]]);
+        let inverse = m.inverse().unwrap();
+        assert_eq!(inverse, Matrix::new([[-2.0, 1.0], [0.5, 0.0]]));
+        assert_eq!(m * inverse, Matrix2::identity());
+        assert_eq!(Matrix::new([[1.0, 2.0], [2.0, 4.0f32]]).inverse(), None);
+        let tiny = Matrix2::from_diagonal(Vector::new([1e-8f32, 1e-8]));
+        assert_eq!(
+            tiny.inverse(),
+            Some(Matrix2::from_diagonal(Vector::new([1e8, 1e8])))
+        );
+        let singular = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0f64]]);
+        assert_eq!(singular.inverse(), None);
+        assert_eq!((singular * 1e-12).inverse(), None);
+
+        let r = Matrix::new([[2, 1], [7, 4]]).map(Rational::integer);
+        let third = Matrix::new([[1, 2], [3, 4]]).map(Rational::integer);
+        assert_eq!(
+            r.inverse_exact(),
+            Some(Matrix::new([[4, -1], [-7, 2]]).map(Rational::integer))
+        );
+        assert_eq!(
+            third.inverse_exact().unwrap().values[1],
+            [Rational::new(3, 2), Rational::new(-1, 2)]
+        );
+
+        assert_eq!(
+            Matrix::new([[2, 1], [7, 4]]).inverse_integer(),
+            Some(Matrix::new([[4, -1], [-7, 2]]))
+        );
+        assert_eq!(Matrix::new([[1i64, 2], [3, 4]]).inverse_integer(), None);
+
+        // NOTE: 2 has no inverse modulo 12, the pivot of the first column is the 1 below it.
+        let m = Matrix::new([[2, 1], [1, 1]].map(|row| row.map(Modi32::<12>::new)));
+        let inverse = m.inverse_exact().unwrap();
+        assert_eq!(
+            inverse,
+            Matrix::new([[1, -1], [-1, 2]].map(|row| row.map(Modi32::new)))
+        );
+        assert_eq!(m * inverse, Matrix2::identity());
+        assert_eq!(Matrix::new([[1i8, 1], [1, 1]]).inverse_integer(), None);
+    }
+
+    #[test]
+    fn transforms() {
+        let quarter = std::f64::consts::FRAC_PI_2;
+        let rotation = Matrix3::new([
+            [quarter.cos(), -quarter.sin(), 0.0],
+            [quarter.sin(), quarter.cos(), 0.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let translation = Matrix3::from_translation(V2::from_xy(10.0, 0.0));
+        let composed = translation * rotation;
+
+        let p = V2::from_xy(1.0, 0.0);
+        assert!(composed
+            .transform_point(p)
+            .approx_eq(V2::from_xy(10.0, 1.0), 1e-12));
+        let composed = InvertibleMatrix::new(composed).unwrap();
+        assert!(composed
+            .transform(p)
+            .approx_eq(V2::from_xy(10.0, 1.0), 1e-12));
+        assert!(composed
+            .inverse_transform(V2::from_xy(10.0, 1.0))
+            .approx_eq(p, 1e-12));
+        assert!(composed
+            .inverted()
+            .transform(V2::from_xy(10.0, 1.0))
+            .approx_eq(p, 1e-12));
+
+        let scale = Matrix::from_diagonal(V3::from_xyz(2.0, 3.0, 4.0f32));
+        let scale = InvertibleMatrix::new(scale).unwrap();
+        assert_eq!(scale.transform(V3::all(1.0)), V3::from_xyz(2.0, 3.0, 4.0));
+        assert_eq!(
+            scale.inverse_transform(V3::from_xyz(2.0, 3.0, 4.0)),
+            V3::all(1.0)
+        );
+
+        let projection = Matrix::from_diagonal(V3::from_xyz(1.0, 1.0, 0.0f32));
+        assert_eq!(InvertibleMatrix::new(projection), None);
+    }
+}
@@ -1,11 +1,16 @@
 use std::fmt::Display;
 use std::ops::Add;
 use std::ops::AddAssign;
+use std::ops::Div;
 use std::ops::Mul;
+use std::ops::Neg;
 use std::ops::Sub;
 use std::ops::SubAssign;
 
-use super::vector::Vector;
+use super::{
+    math::{Field, One, Zero},
+    vector::Vector,
+};
 
 pub trait ModularDecompose<T> {
     fn modular_decompose(&self, n: T) -> (T, T);
@@ -123,7 +128,7 @@ impl<const C: usize, T: Copy + ModularSubAssign> ModularSubAssign for Vector<C,
 
 macro_rules! modular_type {
     ($n:ident, $t:ty) => {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub struct $n<const C: $t>($t);
 
         impl<const C: $t> $n<C> {
@@ -134,6 +139,30 @@ macro_rules! modular_type {
             pub fn get(&self) -> $t {
                 self.0
             }
+
+            pub fn pow(&self, mut exponent: u64) -> Self {
+                let (mut base, mut result) = (*self, Self::new(1));
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        result = result * base;
+                    }
+                    base = base * base;
+                    exponent >>= 1;
+                }
+                result
+            }
+
+            // NOTE: Extended Euclid, `None` unless the value is coprime with `C`.
+            pub fn inverse(&self) -> Option<Self> {
+                let (mut a, mut b) = (self.0 as i128, C as i128);
+                let (mut x, mut y) = (1i128, 0i128);
+                while b != 0 {
+                    let q = a / b;
+                    (a, b) = (b, a - q * b);
+                    (x, y) = (y, x - q * y);
+                }
+                (a == 1 || C == 1).then(|| Self(x.rem_euclid(C as i128) as $t))
+            }
         }
 
         impl<const C: $t> ModularDecompose<$t> for $n<C> {
@@ -175,6 +204,37 @@ macro_rules! modular_type {
             }
         }
 
+        impl<const C: $t> Neg for $n<C> {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(0) - self
+            }
+        }
+
+        // NOTE: Panics when `rhs` has no inverse, which for prime `C` only happens for zero.
+        #[allow(clippy::suspicious_arithmetic_impl)]
+        impl<const C: $t> Div for $n<C> {
+            type Output = Self;
+            fn div(self, rhs: Self) -> Self {
+                self * rhs.inverse().expect("division by a non-invertible value")
+            }
+        }
+
+        impl<const C: $t> Zero for $n<C> {
+            fn zero() -> Self {
+                Self::new(0)
+            }
+        }
+
+        impl<const C: $t> One for $n<C> {
+            fn one() -> Self {
+                Self::new(1)
+            }
+        }
+
+        // NOTE: Only a field when `C` is prime.
+        impl<const C: $t> Field for $n<C> {}
+
         impl<const C: $t> Display for $n<C> {
             fn fmt(
                 &self,
@@ -211,4 +271,16 @@ mod tests {
         let b = Modusize(4);
         assert_eq!(a + b, Modusize(2));
     }
+
+    #[test]
+    fn modular_field_test() {
+        let a = Modi32::<7>::new(3);
+        assert_eq!(a.inverse(), Some(Modi32(5)));
+        assert_eq!(a / a, Modi32(1));
+        assert_eq!(Modi32::<7>::new(2) / a, Modi32(3));
+        assert_eq!(-a, Modi32(4));
+        assert_eq!(a.pow(6), Modi32(1));
+        assert_eq!(Modusize::<12>::new(4).inverse(), None);
+        assert_eq!(Modusize::<12>::new(5).inverse(), Some(Modusize(5)));
+    }
 }
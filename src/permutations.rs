@@ -1,7 +1,7 @@
 use std::ops::Mul;
 
 #[derive(Debug, PartialEq, Eq)]
-struct Perm<const M: usize>([usize; M]);
+pub(crate) struct Perm<const M: usize>(pub(crate) [usize; M]);
 
 impl<const M: usize> Perm<M> {
     fn chain(&self, other: &Self) -> Self
@@ -28,7 +28,7 @@ impl<const M: usize> Mul for Perm<M> {
 }
 
 #[derive(Debug)]
-struct PermId<const M: usize>(usize);
+pub(crate) struct PermId<const M: usize>(pub(crate) usize);
 
 impl<const M: usize> PermId<M> {
     pub fn perm(self) -> Perm<M> {
@@ -1,6 +1,12 @@
 pub use crate::aabb::{Aabb, Aabb2, Aabb3, Aabb4};
-pub use crate::arraynd::{Array2d, Array3d, ArrayNd, CharArray2d};
+pub use crate::array_view::{ArrayView, ArrayViewMut, Dihedral};
+pub use crate::arraynd::{Array2d, Array3d, ArrayNd, CharArray2d, CharArray3d, Torus};
+pub use crate::chunked_grid::ChunkedGrid;
 pub use crate::geometric_traits::*;
 pub use crate::line::{LineV2i32, LineVector, LineVectori32};
+pub use crate::line_iterator::LineMode;
 pub use crate::linear_index::LinearIndex;
+pub use crate::matrix::{InvertibleMatrix, Matrix, Matrix2, Matrix3, Matrix4};
+pub use crate::raster::FillRule;
+pub use crate::sparse_grid::SparseGrid;
 pub use crate::vector::{V2i32, Vector, V2, V3, V4};
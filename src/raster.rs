@@ -0,0 +1,416 @@
+use std::ops::{Add, Mul};
+
+use super::{
+    aabb::{Aabb, Aabb2},
+    arraynd::Array2d,
+    geometric_traits::CoverObject,
+    vector::{V2i32, Vector},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    // NOTE: Inside if a ray from the point crosses the outline an odd number of times.
+    EvenOdd,
+    // NOTE: Inside if the outline winds around the point at least once.
+    NonZero,
+}
+
+// NOTE: Writes cells that lie inside the array and remembers the box covering them.
+struct Touched(Option<Aabb2<i32>>);
+
+impl Touched {
+    fn set<T: Copy>(&mut self, array: &mut Array2d<T>, p: V2i32, v: T) {
+        if array.set(p, v) {
+            match &mut self.0 {
+                Some(bounds) => bounds.cover(&p),
+                None => self.0 = Some(Aabb::new(p, p)),
+            }
+        }
+    }
+
+    fn span<T: Copy>(&mut self, array: &mut Array2d<T>, y: i32, x0: i32, x1: i32, v: T) {
+        if y < 0 || y >= array.height() as i32 {
+            return;
+        }
+        let x0 = x0.max(0);
+        let x1 = x1.min(array.width() as i32 - 1);
+        for x in x0..=x1 {
+            self.set(array, V2i32::from_xy(x, y), v);
+        }
+    }
+}
+
+// NOTE: All shapes are clipped to the array and return the box covering the cells they wrote,
+// `None` if nothing was written.
+impl<T: Copy> Array2d<T> {
+    // NOTE: Midpoint circle algorithm.
+    pub fn draw_circle(&mut self, center: V2i32, radius: i32, v: T) -> Option<Aabb2<i32>> {
+        let mut touched = Touched(None);
+        let (mut x, mut y, mut error) = (radius, 0, 1 - radius);
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x)] {
+                for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+                    touched.set(self, center + V2i32::from_xy(sx * dx, sy * dy), v);
+                }
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+        touched.0
+    }
+
+    pub fn fill_circle(&mut self, center: V2i32, radius: i32, v: T) -> Option<Aabb2<i32>> {
+        let mut touched = Touched(None);
+        let (mut x, mut y, mut error) = (radius, 0, 1 - radius);
+        let [cx, cy] = center.values;
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x)] {
+                touched.span(self, cy + dy, cx - dx, cx + dx, v);
+                touched.span(self, cy - dy, cx - dx, cx + dx, v);
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+        touched.0
+    }
+
+    // NOTE: Midpoint ellipse algorithm, calls `plot(dx, dy)` for points of the first quadrant.
+    fn ellipse_quadrant<F: FnMut(&mut Self, &mut Touched, i32, i32)>(
+        &mut self,
+        radii: V2i32,
+        mut plot: F,
+    ) -> Option<Aabb2<i32>> {
+        let mut touched = Touched(None);
+        let [rx, ry] = radii.values;
+        if rx < 0 || ry < 0 {
+            return None;
+        }
+        if ry == 0 {
+            for x in 0..=rx {
+                plot(self, &mut touched, x, 0);
+            }
+            return touched.0;
+        }
+
+        let (rx2, ry2) = ((rx * rx) as f64, (ry * ry) as f64);
+        let (mut x, mut y) = (0, ry);
+        let mut dx = 0.0;
+        let mut dy = 2.0 * rx2 * y as f64;
+
+        let mut p = ry2 - rx2 * ry as f64 + rx2 / 4.0;
+        while dx < dy {
+            plot(self, &mut touched, x, y);
+            x += 1;
+            dx += 2.0 * ry2;
+            if p < 0.0 {
+                p += dx + ry2;
+            } else {
+                y -= 1;
+                dy -= 2.0 * rx2;
+                p += dx - dy + ry2;
+            }
+        }
+
+        let mut p = ry2 * (x as f64 + 0.5).powi(2) + rx2 * ((y - 1) as f64).powi(2) - rx2 * ry2;
+        while y >= 0 {
+            plot(self, &mut touched, x, y);
+            y -= 1;
+            dy -= 2.0 * rx2;
+            if p > 0.0 {
+                p += rx2 - dy;
+            } else {
+                x += 1;
+                dx += 2.0 * ry2;
+                p += dx - dy + rx2;
+            }
+        }
+        touched.0
+    }
+
+    pub fn draw_ellipse(&mut self, center: V2i32, radii: V2i32, v: T) -> Option<Aabb2<i32>> {
+        self.ellipse_quadrant(radii, |array, touched, x, y| {
+            for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+                touched.set(array, center + V2i32::from_xy(sx * x, sy * y), v);
+            }
+        })
+    }
+
+    pub fn fill_ellipse(&mut self, center: V2i32, radii: V2i32, v: T) -> Option<Aabb2<i32>> {
+        let [cx, cy] = center.values;
+        self.ellipse_quadrant(radii, |array, touched, x, y| {
+            touched.span(array, cy + y, cx - x, cx + x, v);
+            touched.span(array, cy - y, cx - x, cx + x, v);
+        })
+    }
+
+    // NOTE: Scanline fill sampling cell centres. Edges are half-open, so polygons sharing an
+    // edge do not overlap: the square `(0, 0), (4, 0), (4, 4), (0, 4)` fills 4x4 cells.
+    pub fn fill_polygon(&mut self, points: &[V2i32], rule: FillRule, v: T) -> Option<Aabb2<i32>> {
+        let mut touched = Touched(None);
+        let bounds = Aabb::covering(points)?;
+        let y_min = bounds.min.y().max(0);
+        let y_max = bounds.max.y().min(self.height() as i32 - 1);
+
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for y in y_min..=y_max {
+            crossings.clear();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                let winding = match (a.y() <= y && y < b.y(), b.y() <= y && y < a.y()) {
+                    (true, _) => 1,
+                    (_, true) => -1,
+                    _ => continue,
+                };
+                let t = (y - a.y()) as f64 / (b.y() - a.y()) as f64;
+                crossings.push((a.x() as f64 + t * (b.x() - a.x()) as f64, winding));
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut inside = 0;
+            for pair in crossings.windows(2) {
+                inside += match rule {
+                    FillRule::EvenOdd => 1,
+                    FillRule::NonZero => pair[0].1,
+                };
+                let filled = match rule {
+                    FillRule::EvenOdd => inside % 2 == 1,
+                    FillRule::NonZero => inside != 0,
+                };
+                if filled {
+                    let x0 = pair[0].0.ceil() as i32;
+                    let x1 = pair[1].0.ceil() as i32 - 1;
+                    touched.span(self, y, x0, x1, v);
+                }
+            }
+        }
+        touched.0
+    }
+
+    // NOTE: `shade` gets every cell whose centre lies inside the triangle or on its edges, with
+    // the barycentric weights of the three vertices.
+    pub fn fill_triangle_with<F>(
+        &mut self,
+        vertices: [V2i32; 3],
+        mut shade: F,
+    ) -> Option<Aabb2<i32>>
+    where
+        F: FnMut(V2i32, [f32; 3]) -> T,
+    {
+        let mut touched = Touched(None);
+        let [a, b, c] = vertices;
+        let edge = |p: V2i32, q: V2i32, r: V2i32| (q - p).winding(r - p) as i64;
+        let area = edge(a, b, c);
+        if area == 0 {
+            return None;
+        }
+
+        let bounds = Aabb::covering(&vertices)?;
+        let min = bounds.min.elementwise_max(Vector::all(0));
+        let max = V2i32::from_xy(
+            bounds.max.x().min(self.width() as i32 - 1),
+            bounds.max.y().min(self.height() as i32 - 1),
+        );
+        for y in min.y()..=max.y() {
+            for x in min.x()..=max.x() {
+                let p = V2i32::from_xy(x, y);
+                let w = [edge(b, c, p), edge(c, a, p), edge(a, b, p)];
+                if w.iter().all(|w| w * area.signum() >= 0) {
+                    let weights = w.map(|w| w as f32 / area as f32);
+                    let v = shade(p, weights);
+                    touched.set(self, p, v);
+                }
+            }
+        }
+        touched.0
+    }
+
+    pub fn fill_triangle(&mut self, vertices: [V2i32; 3], v: T) -> Option<Aabb2<i32>> {
+        self.fill_triangle_with(vertices, |_, _| v)
+    }
+
+    // NOTE: Works for any value that can be scaled by `f32` and summed, like `f32` or
+    // `Vector<3, f32>` colours.
+    pub fn fill_triangle_interpolated(
+        &mut self,
+        vertices: [V2i32; 3],
+        values: [T; 3],
+    ) -> Option<Aabb2<i32>>
+    where
+        T: Add<Output = T> + Mul<f32, Output = T>,
+    {
+        self.fill_triangle_with(vertices, |_, [wa, wb, wc]| {
+            values[0] * wa + values[1] * wb + values[2] * wc
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FillRule;
+    use crate::{
+        aabb::Aabb2,
+        arraynd::{Array2d, CharArray2d},
+        vector::V2i32,
+    };
+
+    fn canvas(width: usize, height: usize) -> CharArray2d {
+        CharArray2d::with_dimensions(width, height, '.')
+    }
+
+    #[test]
+    fn circles() {
+        let mut c = canvas(7, 7);
+        let bounds = c.draw_circle(V2i32::from_xy(3, 3), 3, '#');
+        assert_eq!(
+            c.to_string(),
+            "..###..\n.#...#.\n#.....#\n#.....#\n#.....#\n.#...#.\n..###..\n"
+        );
+        assert_eq!(
+            bounds,
+            Some(Aabb2::new(V2i32::from_xy(0, 0), V2i32::from_xy(6, 6)))
+        );
+
+        let mut c = canvas(7, 7);
+        c.fill_circle(V2i32::from_xy(3, 3), 3, '#');
+        assert_eq!(
+            c.to_string(),
+            "..###..\n.#####.\n#######\n#######\n#######\n.#####.\n..###..\n"
+        );
+
+        let mut c = canvas(4, 4);
+        let bounds = c.fill_circle(V2i32::from_xy(0, 0), 2, '#');
+        assert_eq!(c.to_string(), "###.\n###.\n##..\n....\n");
+        assert_eq!(
+            bounds,
+            Some(Aabb2::new(V2i32::from_xy(0, 0), V2i32::from_xy(2, 2)))
+        );
+
+        let mut c = canvas(3, 3);
+        assert_eq!(c.draw_circle(V2i32::from_xy(20, 20), 2, '#'), None);
+        assert_eq!(
+            c.draw_circle(V2i32::from_xy(1, 1), 0, '#'),
+            Some(Aabb2::new(V2i32::from_xy(1, 1), V2i32::from_xy(1, 1)))
+        );
+    }
+
+    #[test]
+    fn ellipses() {
+        let mut c = canvas(9, 5);
+        let bounds = c.draw_ellipse(V2i32::from_xy(4, 2), V2i32::from_xy(4, 2), '#');
+        assert_eq!(
+            c.to_string(),
+            "..#####..\n.#.....#.\n#.......#\n.#.....#.\n..#####..\n"
+        );
+        assert_eq!(
+            bounds,
+            Some(Aabb2::new(V2i32::from_xy(0, 0), V2i32::from_xy(8, 4)))
+        );
+
+        let mut c = canvas(9, 5);
+        c.fill_ellipse(V2i32::from_xy(4, 2), V2i32::from_xy(4, 2), '#');
+        assert_eq!(
+            c.to_string(),
+            "..#####..\n.#######.\n#########\n.#######.\n..#####..\n"
+        );
+
+        let mut c = canvas(5, 3);
+        c.fill_ellipse(V2i32::from_xy(2, 1), V2i32::from_xy(2, 0), '#');
+        assert_eq!(c.to_string(), ".....\n#####\n.....\n");
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [
+            V2i32::from_xy(1, 1),
+            V2i32::from_xy(5, 1),
+            V2i32::from_xy(5, 5),
+            V2i32::from_xy(1, 5),
+        ];
+        let mut c = canvas(7, 7);
+        let bounds = c.fill_polygon(&square, FillRule::EvenOdd, '#');
+        assert_eq!(
+            bounds,
+            Some(Aabb2::new(V2i32::from_xy(1, 1), V2i32::from_xy(4, 4)))
+        );
+        assert_eq!(c.iter().filter(|x| **x == '#').count(), 16);
+
+        // NOTE: Two overlapping squares traced in the same direction, the overlap winds twice.
+        let overlapping = [
+            V2i32::from_xy(0, 0),
+            V2i32::from_xy(4, 0),
+            V2i32::from_xy(4, 4),
+            V2i32::from_xy(2, 4),
+            V2i32::from_xy(2, 2),
+            V2i32::from_xy(6, 2),
+            V2i32::from_xy(6, 6),
+            V2i32::from_xy(0, 6),
+        ];
+        let mut even_odd = canvas(6, 6);
+        even_odd.fill_polygon(&overlapping, FillRule::EvenOdd, '#');
+        let mut non_zero = canvas(6, 6);
+        non_zero.fill_polygon(&overlapping, FillRule::NonZero, '#');
+        assert_eq!(
+            non_zero.to_string(),
+            "####..\n####..\n######\n######\n######\n######\n"
+        );
+        assert_eq!(
+            even_odd.to_string(),
+            "####..\n####..\n##..##\n##..##\n######\n######\n"
+        );
+
+        let mut c = canvas(3, 3);
+        assert_eq!(c.fill_polygon(&[], FillRule::NonZero, '#'), None);
+    }
+
+    #[test]
+    fn triangles() {
+        let mut c = canvas(5, 5);
+        let vertices = [
+            V2i32::from_xy(0, 0),
+            V2i32::from_xy(4, 0),
+            V2i32::from_xy(0, 4),
+        ];
+        let bounds = c.fill_triangle(vertices, '#');
+        assert_eq!(c.to_string(), "#####\n####.\n###..\n##...\n#....\n");
+        assert_eq!(
+            bounds,
+            Some(Aabb2::new(V2i32::from_xy(0, 0), V2i32::from_xy(4, 4)))
+        );
+
+        // NOTE: Winding order does not matter.
+        let mut reversed = canvas(5, 5);
+        reversed.fill_triangle([vertices[2], vertices[1], vertices[0]], '#');
+        assert_eq!(reversed, c);
+
+        let mut values = Array2d::with_dimensions(5, 5, -1.0f32);
+        values.fill_triangle_interpolated(vertices, [0.0, 4.0, 8.0]);
+        assert_eq!(values.get(V2i32::from_xy(0, 0)), Some(&0.0));
+        assert_eq!(values.get(V2i32::from_xy(4, 0)), Some(&4.0));
+        assert_eq!(values.get(V2i32::from_xy(0, 4)), Some(&8.0));
+        assert_eq!(values.get(V2i32::from_xy(1, 2)), Some(&5.0));
+        assert_eq!(values.get(V2i32::from_xy(4, 4)), Some(&-1.0));
+
+        let mut c = canvas(5, 5);
+        assert_eq!(
+            c.fill_triangle(
+                [
+                    V2i32::from_xy(0, 0),
+                    V2i32::from_xy(2, 2),
+                    V2i32::from_xy(4, 4)
+                ],
+                '#'
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use super::math::{Field, Gcd, One, Zero};
+
+// NOTE: Exact fraction of two `i128`, always kept reduced with a positive denominator. The wide
+// integers leave room for eliminating systems with coefficients around `1e14`, beyond that
+// arithmetic overflows like the underlying integers do.
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+impl Rational {
+    // NOTE: Reduces in `u128`, so `i128::MIN` is fine as long as the reduced fraction fits.
+    pub fn new(numer: i128, denom: i128) -> Self {
+        assert!(denom != 0, "rational with zero denominator");
+        let negative = (numer < 0) != (denom < 0);
+        let gcd = u128::gcd(numer.unsigned_abs(), denom.unsigned_abs());
+        let (numer, denom) = (numer.unsigned_abs() / gcd, denom.unsigned_abs() / gcd);
+        let numer = match negative {
+            true => 0i128.checked_sub_unsigned(numer),
+            false => i128::try_from(numer).ok(),
+        };
+        Self {
+            numer: numer.expect("rational out of range"),
+            denom: i128::try_from(denom).expect("rational out of range"),
+        }
+    }
+
+    pub const fn integer(value: i128) -> Self {
+        Self {
+            numer: value,
+            denom: 1,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numer)
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.denom, self.numer)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::integer(value.into())
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self.numer.checked_mul(other.denom);
+        let rhs = other.numer.checked_mul(self.denom);
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            return lhs.cmp(&rhs);
+        }
+        // NOTE: The cross products overflow, so compare the continued fraction expansions
+        // instead. Integer parts first, then the reciprocals of the remainders, which flips
+        // the order at every step.
+        let (mut a, mut b, mut c, mut d) = (self.numer, self.denom, other.numer, other.denom);
+        let mut reversed = false;
+        let ordering = loop {
+            let (qa, qc) = (a.div_euclid(b), c.div_euclid(d));
+            if qa != qc {
+                break qa.cmp(&qc);
+            }
+            let (ra, rc) = (a.rem_euclid(b), c.rem_euclid(d));
+            if ra == 0 || rc == 0 {
+                break (ra != 0).cmp(&(rc != 0));
+            }
+            (a, b, c, d) = (b, ra, d, rc);
+            reversed = !reversed;
+        };
+        match reversed {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    }
+}
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let lcm = i128::lcm(self.denom, rhs.denom);
+        Self::new(
+            self.numer * (lcm / self.denom) + rhs.numer * (lcm / rhs.denom),
+            lcm,
+        )
+    }
+}
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // NOTE: Cross-reducing first keeps the intermediate products small.
+        let a = gcd(self.numer, rhs.denom);
+        let b = gcd(rhs.numer, self.denom);
+        Self::new(
+            (self.numer / a) * (rhs.numer / b),
+            (self.denom / b) * (rhs.denom / a),
+        )
+    }
+}
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.recip()
+    }
+}
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl AddAssign for Rational {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl SubAssign for Rational {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+impl MulAssign for Rational {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+impl DivAssign for Rational {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl Zero for Rational {
+    fn zero() -> Self {
+        Self::integer(0)
+    }
+}
+impl One for Rational {
+    fn one() -> Self {
+        Self::integer(1)
+    }
+}
+impl Field for Rational {}
+
+// NOTE: Only called with a positive denominator, so the result always fits.
+fn gcd(numer: i128, denom: i128) -> i128 {
+    u128::gcd(numer.unsigned_abs(), denom.unsigned_abs()) as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rational;
+
+    #[test]
+    fn arithmetic() {
+        let half = Rational::new(1, 2);
+        let third = Rational::new(-2, -6);
+        assert_eq!(third, Rational::new(1, 3));
+        assert_eq!(Rational::new(3, -6), -half);
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert_eq!(Rational::new(0, -5), Rational::integer(0));
+        assert_eq!((half + half).to_integer(), Some(1));
+        assert_eq!(half.to_integer(), None);
+        assert!(third < half && -half < third);
+        assert_eq!(Rational::new(-7, 4).to_string(), "-7/4");
+        assert_eq!(Rational::from(12).to_string(), "12");
+    }
+
+    #[test]
+    fn extremes() {
+        let close = Rational::new(i128::MAX / 2, i128::MAX / 2 - 1);
+        assert!(Rational::new(1, 3) < close);
+        assert!(close < Rational::new(i128::MAX / 2 - 1, i128::MAX / 2 - 2));
+        assert!(-close < Rational::new(-1, 3));
+        assert_eq!(close.cmp(&close), std::cmp::Ordering::Equal);
+        let big = Rational::new(i128::MAX, 3);
+        assert!(Rational::new(i128::MAX - 1, 3) < big);
+        assert!(Rational::new(i128::MAX, 2) > big);
+
+        assert_eq!(Rational::new(i128::MIN, 1), Rational::integer(i128::MIN));
+        assert_eq!(Rational::new(i128::MIN, -2), Rational::integer(1 << 126));
+        assert_eq!(Rational::new(6, i128::MIN), Rational::new(-3, 1 << 126));
+        assert_eq!(Rational::new(i128::MIN, i128::MIN), Rational::integer(1));
+        assert!(Rational::new(i128::MIN, 1) < Rational::new(i128::MIN + 1, 1));
+        assert_eq!(
+            Rational::new(i128::MIN, 1) * Rational::new(1, 2),
+            Rational::new(i128::MIN, 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "rational out of range")]
+    fn unrepresentable() {
+        Rational::new(i128::MIN, -1);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{collections::HashMap, fmt::Display};
+
+use super::{
+    aabb::Aabb,
+    arraynd::ArrayNd,
+    geometric_traits::{CoverObject, IterateNeighbours, IterateNeighboursContext},
+    linear_index::LinearIndex,
+    vector::Vector,
+};
+
+// NOTE: Unbounded grid that only stores cells differing from `default`. Every other position reads
+// as `default`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseGrid<const N: usize, T> {
+    cells: HashMap<Vector<N, i32>, T>,
+    default: T,
+    bounds: Option<Aabb<N, i32>>,
+}
+
+impl<const N: usize, T> SparseGrid<N, T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    // NOTE: Smallest box covering all stored cells, `None` while the grid is empty.
+    pub fn bounds(&self) -> Option<Aabb<N, i32>> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, p: Vector<N, i32>) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn get(&self, p: Vector<N, i32>) -> &T {
+        self.cells.get(&p).unwrap_or(&self.default)
+    }
+
+    pub fn remove(&mut self, p: Vector<N, i32>) -> Option<T> {
+        let removed = self.cells.remove(&p)?;
+        let bounds = self.bounds.unwrap();
+        let on_border = (0..N)
+            .any(|i| p.values[i] == bounds.min.values[i] || p.values[i] == bounds.max.values[i]);
+        if on_border {
+            self.bounds = Aabb::covering(&self.cells.keys().copied().collect::<Vec<_>>());
+        }
+        Some(removed)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds = None;
+    }
+
+    // NOTE: Stored cells only, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector<N, i32>, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    fn cover(&mut self, p: Vector<N, i32>) {
+        match &mut self.bounds {
+            Some(bounds) => bounds.cover(&p),
+            None => self.bounds = Some(Aabb::new(p, p)),
+        }
+    }
+
+    // NOTE: Positions of the stored cells matching `predicate`, in the linear order an `ArrayNd`
+    // would use (first axis changing fastest).
+    pub fn find_all<F>(&self, predicate: F) -> Vec<Vector<N, i32>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut result: Vec<Vector<N, i32>> = self
+            .cells
+            .iter()
+            .filter(|(_, v)| predicate(v))
+            .map(|(p, _)| *p)
+            .collect();
+        result.sort_by(|a, b| a.values.iter().rev().cmp(b.values.iter().rev()));
+        result
+    }
+
+    pub fn find_all_items(&self, item: &T) -> Vec<Vector<N, i32>>
+    where
+        T: PartialEq,
+    {
+        self.find_all(|x| x == item)
+    }
+
+    pub fn find_item(&self, item: &T) -> Option<Vector<N, i32>>
+    where
+        T: PartialEq,
+    {
+        self.find_all_items(item).first().copied()
+    }
+}
+
+impl<const N: usize, T: Clone + PartialEq> SparseGrid<N, T> {
+    // NOTE: Setting a cell to the default value removes it.
+    pub fn set(&mut self, p: Vector<N, i32>, v: T) -> Option<T> {
+        if v == self.default {
+            self.remove(p)
+        } else {
+            self.cover(p);
+            self.cells.insert(p, v)
+        }
+    }
+
+    // NOTE: Stores the cell first if it is not present yet. A cell left at the default value
+    // stays stored until `set` or `remove` is called on it.
+    pub fn get_mut(&mut self, p: Vector<N, i32>) -> &mut T {
+        self.cover(p);
+        self.cells.entry(p).or_insert_with(|| self.default.clone())
+    }
+}
+
+impl<const N: usize, T: Copy + PartialEq> SparseGrid<N, T> {
+    // NOTE: `array[p]` ends up at `p + offset`. Cells equal to `default` are not stored.
+    pub fn from_arraynd(array: &ArrayNd<N, T>, offset: Vector<N, i32>, default: T) -> Self {
+        let mut result = Self::new(default);
+        for (i, v) in array.data.iter().enumerate() {
+            let p: Vector<N, i32> = array.unindex(i).unwrap();
+            result.set(p + offset, *v);
+        }
+        result
+    }
+
+    // NOTE: Copies out the cells inside `bounds` (inclusive), `array[p]` is `self[p + bounds.min]`.
+    // `None` if `bounds.max` is below `bounds.min` on any axis.
+    pub fn to_arraynd_in(&self, bounds: Aabb<N, i32>) -> Option<ArrayNd<N, T>> {
+        if bounds.dim().values.iter().any(|d| *d < 0) {
+            return None;
+        }
+        let dims = (bounds.dim() + Vector::all(1)).values.map(|d| d as usize);
+        let mut result = ArrayNd::new(dims, self.default);
+        for (p, v) in self.iter() {
+            result.set(p - bounds.min, *v);
+        }
+        Some(result)
+    }
+
+    // NOTE: The array covering all stored cells, together with the position of `array[0]`.
+    pub fn to_arraynd(&self) -> Option<(ArrayNd<N, T>, Vector<N, i32>)> {
+        let bounds = self.bounds?;
+        Some((self.to_arraynd_in(bounds)?, bounds.min))
+    }
+}
+
+impl<const N: usize, T: Copy + PartialEq + Display> Display for SparseGrid<N, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_arraynd() {
+            Some((array, _)) => array.fmt(f),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<const N: usize, T> IterateNeighboursContext for SparseGrid<N, T> {}
+
+impl<const N: usize, T> IterateNeighbours<SparseGrid<N, T>> for Vector<N, i32> {
+    fn neighbours(&self, _context: &SparseGrid<N, T>) -> Vec<Self> {
+        self.neighbours(&())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseGrid;
+    use crate::{aabb::Aabb2, arraynd::CharArray2d, vector::V2i32};
+
+    #[test]
+    fn get_set_remove() {
+        let mut grid = SparseGrid::new('.');
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(*grid.get(V2i32::from_xy(100, -100)), '.');
+
+        grid.set(V2i32::from_xy(-2, 3), '#');
+        grid.set(V2i32::from_xy(5, -1), '#');
+        grid.set(V2i32::from_xy(1, 1), 'o');
+        assert_eq!(grid.len(), 3);
+        assert_eq!(
+            grid.bounds(),
+            Some(Aabb2::new(V2i32::from_xy(-2, -1), V2i32::from_xy(5, 3)))
+        );
+
+        assert_eq!(grid.set(V2i32::from_xy(5, -1), '.'), Some('#'));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(
+            grid.bounds(),
+            Some(Aabb2::new(V2i32::from_xy(-2, 1), V2i32::from_xy(1, 3)))
+        );
+
+        *grid.get_mut(V2i32::from_xy(0, 0)) = 'x';
+        assert_eq!(*grid.get(V2i32::from_xy(0, 0)), 'x');
+        assert_eq!(grid.remove(V2i32::from_xy(7, 7)), None);
+        assert_eq!(grid.remove(V2i32::from_xy(-2, 3)), Some('#'));
+        assert_eq!(
+            grid.bounds(),
+            Some(Aabb2::new(V2i32::from_xy(0, 0), V2i32::from_xy(1, 1)))
+        );
+
+        grid.clear();
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn find_and_display() {
+        let mut grid = SparseGrid::new('.');
+        grid.set(V2i32::from_xy(3, -1), '#');
+        grid.set(V2i32::from_xy(-1, 0), '#');
+        grid.set(V2i32::from_xy(0, -1), '#');
+        grid.set(V2i32::from_xy(1, 0), 'S');
+
+        assert_eq!(
+            grid.find_all_items(&'#'),
+            vec![
+                V2i32::from_xy(0, -1),
+                V2i32::from_xy(3, -1),
+                V2i32::from_xy(-1, 0)
+            ]
+        );
+        assert_eq!(grid.find_item(&'S'), Some(V2i32::from_xy(1, 0)));
+        assert_eq!(grid.find_item(&'E'), None);
+        assert_eq!(grid.to_string(), ".#..#\n#.S..\n");
+        assert_eq!(SparseGrid::<2, char>::new('.').to_string(), "");
+    }
+
+    #[test]
+    fn arraynd_round_trip() {
+        let array: CharArray2d = "..#\n#..\n".parse().unwrap();
+        let grid = SparseGrid::from_arraynd(&array, V2i32::from_xy(10, -5), '.');
+        assert_eq!(grid.len(), 2);
+        assert_eq!(*grid.get(V2i32::from_xy(12, -5)), '#');
+        assert_eq!(*grid.get(V2i32::from_xy(10, -4)), '#');
+
+        let (back, offset) = grid.to_arraynd().unwrap();
+        assert_eq!(offset, V2i32::from_xy(10, -5));
+        assert_eq!(back, array);
+
+        let window = grid.to_arraynd_in(Aabb2::new(V2i32::from_xy(9, -5), V2i32::from_xy(10, -4)));
+        assert_eq!(window.unwrap().to_string(), "..\n.#\n");
+        let inverted = Aabb2::new(V2i32::from_xy(10, -4), V2i32::from_xy(9, -5));
+        assert!(grid.to_arraynd_in(inverted).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     fmt::{Debug, Display},
-    ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
+    iter::Sum,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
     str::FromStr,
 };
 
@@ -11,7 +12,7 @@ use super::{
         EuclideanDistanceSquared, IterateNeighbours, ManhattanDistance, Movement4Directions,
     },
     linear_index::LinearIndex,
-    math::AbsoluteValue,
+    math::{AbsoluteValue, Float, One, Zero},
 };
 
 #[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,13 +20,13 @@ pub struct Vector<const C: usize, T> {
     pub values: [T; C],
 }
 
-// impl<const C: usize, T: Default> Default for Vector<C, T> {
-//     fn default() -> Self {
-//         Self {
-//             values: Default::default(),
-//         }
-//     }
-// }
+impl<const C: usize, T: Default> Default for Vector<C, T> {
+    fn default() -> Self {
+        Self {
+            values: std::array::from_fn(|_| T::default()),
+        }
+    }
+}
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -171,7 +172,7 @@ where
         self.elementwise_binary(rhs, |a, b| a.min(b))
     }
     pub fn elementwise_max(&self, rhs: Self) -> Self {
-        self.elementwise_binary(rhs, |a, b| a.min(b))
+        self.elementwise_binary(rhs, |a, b| a.max(b))
     }
 }
 
@@ -196,18 +197,114 @@ impl V2i32 {
     }
 }
 
-impl<const C: usize> Vector<C, f32> {
-    pub fn magn(&self) -> f32 {
+impl<T> V3<T>
+where
+    T: Copy,
+    T: Mul<Output = T>,
+    T: Sub<Output = T>,
+{
+    pub fn cross(&self, rhs: Self) -> Self {
+        let [ax, ay, az] = self.values;
+        let [bx, by, bz] = rhs.values;
+        Self::from_xyz(ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx)
+    }
+}
+
+impl<const C: usize, T: Float> Vector<C, T> {
+    pub fn magn(&self) -> T {
         self.inner(*self).sqrt()
     }
     pub fn normalized(&self) -> Self {
         let magn = self.magn();
 
-        if magn > f32::EPSILON {
-            *self * (1.0 / magn)
+        if magn > T::EPSILON {
+            *self * (T::one() / magn)
+        } else {
+            *self * T::zero()
+        }
+    }
+
+    pub fn distance(&self, rhs: Self) -> T {
+        (rhs - *self).magn()
+    }
+
+    // NOTE: `t = 0` is `self`, `t = 1` is `rhs`, other values extrapolate.
+    pub fn lerp(&self, rhs: Self, t: T) -> Self {
+        *self + (rhs - *self) * t
+    }
+
+    // NOTE: Component of `self` parallel to `onto`, zero if `onto` is zero.
+    pub fn project_onto(&self, onto: Self) -> Self {
+        let length_squared = onto.inner(onto);
+        if length_squared > T::EPSILON {
+            onto * (self.inner(onto) / length_squared)
+        } else {
+            onto * T::zero()
+        }
+    }
+    // NOTE: Component of `self` perpendicular to `onto`.
+    pub fn reject_from(&self, onto: Self) -> Self {
+        *self - self.project_onto(onto)
+    }
+    // NOTE: Mirrors `self` across the hyperplane with the given normal, which does not need to be
+    // normalized.
+    pub fn reflect(&self, normal: Self) -> Self {
+        let projection = self.project_onto(normal);
+        *self - projection - projection
+    }
+
+    // NOTE: Unsigned angle in radians, zero if either vector is zero.
+    pub fn angle_between(&self, rhs: Self) -> T {
+        let magn = self.magn() * rhs.magn();
+        if magn > T::EPSILON {
+            (self.inner(rhs) / magn).clamp(-T::one(), T::one()).acos()
+        } else {
+            T::zero()
+        }
+    }
+
+    pub fn clamp_length(&self, max: T) -> Self {
+        if self.magn() > max {
+            self.normalized() * max
         } else {
-            *self * 0.0
+            *self
+        }
+    }
+
+    pub fn approx_eq(&self, rhs: Self, tolerance: T) -> bool {
+        (0..C).all(|x| self.values[x].approx_eq(rhs.values[x], tolerance))
+    }
+
+    // NOTE: Spherical interpolation, rotates at a constant rate from `self` to `rhs` while
+    // interpolating the length linearly. Falls back to `lerp` for (anti)parallel vectors.
+    pub fn slerp(&self, rhs: Self, t: T) -> Self {
+        let angle = self.angle_between(rhs);
+        let sin = angle.sin();
+        if sin.abs() <= T::EPSILON {
+            return self.lerp(rhs, t);
         }
+        let (from, to) = (self.magn(), rhs.magn());
+        let length = from + (to - from) * t;
+        let direction = self.normalized() * (((T::one() - t) * angle).sin() / sin)
+            + rhs.normalized() * ((t * angle).sin() / sin);
+        direction * length
+    }
+}
+
+impl<T: Float> V2<T> {
+    // NOTE: Unit vector at `angle` radians counterclockwise from the x axis.
+    pub fn from_angle(angle: T) -> Self {
+        Self::from_xy(angle.cos(), angle.sin())
+    }
+    pub fn angle(&self) -> T {
+        self.y().atan2(self.x())
+    }
+    pub fn rotate(&self, angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Self::from_xy(
+            self.x() * cos - self.y() * sin,
+            self.x() * sin + self.y() * cos,
+        )
     }
 }
 
@@ -279,6 +376,143 @@ impl<const C: usize, T: MulAssign + Copy> MulAssign<T> for Vector<C, T> {
     }
 }
 
+impl<const C: usize, T: Neg<Output = T> + Copy> Neg for Vector<C, T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.values.map(|x| -x))
+    }
+}
+
+// NOTE: Division and remainder by a scalar apply to every component.
+macro_rules! scalar_operator {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident) => {
+        impl<const C: usize, T: $op<Output = T> + Copy> $op<T> for Vector<C, T> {
+            type Output = Self;
+
+            fn $method(self, rhs: T) -> Self::Output {
+                Self::new(self.values.map(|x| x.$method(rhs)))
+            }
+        }
+        impl<const C: usize, T: $op_assign + Copy> $op_assign<T> for Vector<C, T> {
+            fn $method_assign(&mut self, rhs: T) {
+                for x in 0..C {
+                    self.values[x].$method_assign(rhs);
+                }
+            }
+        }
+    };
+}
+
+scalar_operator!(Div, div, DivAssign, div_assign);
+scalar_operator!(Rem, rem, RemAssign, rem_assign);
+
+// NOTE: Componentwise (Hadamard) product, quotient and remainder of two vectors.
+macro_rules! elementwise_operator {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident) => {
+        impl<const C: usize, T: $op<Output = T> + Copy> $op for Vector<C, T> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.elementwise_binary(rhs, |a, b| a.$method(b))
+            }
+        }
+        impl<const C: usize, T: $op_assign + Copy> $op_assign for Vector<C, T> {
+            fn $method_assign(&mut self, rhs: Self) {
+                for x in 0..C {
+                    self.values[x].$method_assign(rhs.values[x]);
+                }
+            }
+        }
+    };
+}
+
+elementwise_operator!(Mul, mul, MulAssign, mul_assign);
+elementwise_operator!(Div, div, DivAssign, div_assign);
+elementwise_operator!(Rem, rem, RemAssign, rem_assign);
+
+// NOTE: `&a + &b`, `a + &b` and `&a + b` forward to the by-value operators, `Vector` is `Copy`
+// whenever `T` is.
+macro_rules! reference_operator {
+    ($op:ident, $method:ident, $rhs:ty) => {
+        impl<'a, const C: usize, T: $op<Output = T> + Copy> $op<&'a $rhs> for Vector<C, T> {
+            type Output = Self;
+
+            fn $method(self, rhs: &'a $rhs) -> Self::Output {
+                self.$method(*rhs)
+            }
+        }
+        impl<'a, const C: usize, T: $op<Output = T> + Copy> $op<$rhs> for &'a Vector<C, T> {
+            type Output = Vector<C, T>;
+
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                (*self).$method(rhs)
+            }
+        }
+        impl<'a, 'b, const C: usize, T: $op<Output = T> + Copy> $op<&'b $rhs> for &'a Vector<C, T> {
+            type Output = Vector<C, T>;
+
+            fn $method(self, rhs: &'b $rhs) -> Self::Output {
+                (*self).$method(*rhs)
+            }
+        }
+    };
+}
+
+reference_operator!(Add, add, Vector<C, T>);
+reference_operator!(Sub, sub, Vector<C, T>);
+reference_operator!(Mul, mul, Vector<C, T>);
+reference_operator!(Div, div, Vector<C, T>);
+reference_operator!(Rem, rem, Vector<C, T>);
+reference_operator!(Mul, mul, T);
+reference_operator!(Div, div, T);
+reference_operator!(Rem, rem, T);
+
+impl<const C: usize, T: Neg<Output = T> + Copy> Neg for &Vector<C, T> {
+    type Output = Vector<C, T>;
+
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
+
+// NOTE: Not imported by name, `index` would clash with `LinearIndex::index` in glob imports.
+impl<const C: usize, T> std::ops::Index<usize> for Vector<C, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+impl<const C: usize, T> std::ops::IndexMut<usize> for Vector<C, T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.values[index]
+    }
+}
+
+impl<const C: usize, T: Zero + Add<Output = T> + Copy> Sum for Vector<C, T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, x| acc + x)
+    }
+}
+impl<'a, const C: usize, T: Zero + Add<Output = T> + Copy> Sum<&'a Vector<C, T>> for Vector<C, T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, x| acc + *x)
+    }
+}
+
+impl<const C: usize, T: Zero + Copy> Zero for Vector<C, T> {
+    fn zero() -> Self {
+        Self::all(T::zero())
+    }
+}
+// NOTE: The identity of the componentwise product, not a unit vector.
+impl<const C: usize, T: One + Copy> One for Vector<C, T> {
+    fn one() -> Self {
+        Self::all(T::one())
+    }
+}
+
 impl<const C: usize, T> ModularDecompose<Vector<C, T>> for Vector<C, T>
 where
     T: ModularDecompose<T> + Copy,
@@ -616,6 +850,89 @@ mod tests {
         assert_eq!(V2i32::X, V2i32::X + V2i32::ZERO);
     }
 
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn operators() {
+        let a = V3::from_xyz(6, -4, 9);
+        let b = V3::from_xyz(3, 2, -4);
+        assert_eq!(-a, V3::from_xyz(-6, 4, -9));
+        assert_eq!(-&a, -a);
+        assert_eq!(a / 2, V3::from_xyz(3, -2, 4));
+        assert_eq!(a % 4, V3::from_xyz(2, 0, 1));
+        assert_eq!(a * b, V3::from_xyz(18, -8, -36));
+        assert_eq!(a / b, V3::from_xyz(2, -2, -2));
+        assert_eq!(a % b, V3::from_xyz(0, 0, 1));
+        assert_eq!(&a + &b, a + b);
+        assert_eq!(&a - b, a - b);
+        assert_eq!(a * &b, a * b);
+        assert_eq!(&a * 3, a * 3);
+
+        let mut c = a;
+        c /= 3;
+        c *= V3::from_xyz(1, 2, 3);
+        c %= V3::from_xyz(5, 5, 5);
+        assert_eq!(c, V3::from_xyz(2, -2, 4));
+        c[1] = 7;
+        assert_eq!((c[0], c[1], c[2]), (2, 7, 4));
+    }
+
+    #[test]
+    fn cross_sum_and_identities() {
+        let x = V3::from_xyz(1, 0, 0);
+        let y = V3::from_xyz(0, 1, 0);
+        assert_eq!(x.cross(y), V3::from_xyz(0, 0, 1));
+        assert_eq!(y.cross(x), V3::from_xyz(0, 0, -1));
+        let a = V3::from_xyz(2.0, -1.0, 3.0);
+        let b = V3::from_xyz(0.5, 4.0, 1.0);
+        assert_eq!(a.cross(b).inner(a), 0.0);
+
+        let points = [V2::from_xy(1, 2), V2::from_xy(3, 4), V2::from_xy(-1, 0)];
+        assert_eq!(points.iter().sum::<V2<i32>>(), V2::from_xy(3, 6));
+        assert_eq!(points.into_iter().sum::<V2<i32>>(), V2::from_xy(3, 6));
+        assert_eq!(std::iter::empty::<V2<i32>>().sum::<V2<i32>>(), V2::all(0));
+
+        assert_eq!(V3::<f32>::default(), V3::zero());
+        assert_eq!(V2::<i32>::one() * V2::from_xy(4, 5), V2::from_xy(4, 5));
+        assert_eq!(
+            V2::from_xy(1, 5).elementwise_max(V2::from_xy(3, 2)),
+            V2::from_xy(3, 5)
+        );
+    }
+
+    #[test]
+    fn float_geometry() {
+        let a = V2::from_xy(3.0f64, 4.0);
+        let b = V2::from_xy(1.0, 0.0);
+        assert_eq!(a.magn(), 5.0);
+        assert_eq!(a.distance(b), 20.0f64.sqrt());
+        assert_eq!(a.lerp(b, 0.5), V2::from_xy(2.0, 2.0));
+        assert_eq!(a.project_onto(b), V2::from_xy(3.0, 0.0));
+        assert_eq!(a.reject_from(b), V2::from_xy(0.0, 4.0));
+        assert_eq!(a.reflect(V2::from_xy(0.0, -2.0)), V2::from_xy(3.0, -4.0));
+        assert_eq!(a.project_onto(V2::all(0.0)), V2::all(0.0));
+        assert!(a.clamp_length(1.0).approx_eq(V2::from_xy(0.6, 0.8), 1e-12));
+        assert_eq!(b.clamp_length(2.0), b);
+
+        let quarter = std::f64::consts::FRAC_PI_2;
+        assert!((b.angle_between(V2::from_xy(0.0, -3.0)) - quarter).abs() < 1e-12);
+        assert_eq!(b.angle_between(V2::all(0.0)), 0.0);
+        assert!(b.rotate(quarter).approx_eq(V2::from_xy(0.0, 1.0), 1e-12));
+        assert!(V2::from_angle(quarter).approx_eq(V2::from_xy(0.0, 1.0), 1e-12));
+        assert!((V2::from_xy(-1.0, 1.0).angle() - 3.0 * quarter / 2.0).abs() < 1e-12);
+
+        let x = V3::from_xyz(2.0f32, 0.0, 0.0);
+        let y = V3::from_xyz(0.0, 4.0, 0.0);
+        let halfway = x.slerp(y, 0.5);
+        let diagonal = 3.0 / 2.0f32.sqrt();
+        assert!(halfway.approx_eq(V3::from_xyz(diagonal, diagonal, 0.0), 1e-5));
+        assert!(x.slerp(y, 1.0).approx_eq(y, 1e-5));
+        assert_eq!(x.slerp(x * 3.0, 0.5), x * 2.0);
+        assert!(x
+            .normalized()
+            .cross(y.normalized())
+            .approx_eq(V3::from_xyz(0.0, 0.0, 1.0), 1e-6));
+    }
+
     #[test]
     fn v3_linear_index() {
         let bitmap = V2::from_xy(8, 8);